@@ -5,11 +5,42 @@ declare_id!("6AZSAhq4iJTwCfGEVssoa1p3GnBqGkbcQ1iDdP1U1pSb");
 // =============================================================================
 // MoltLaunch V3 — Composable Signal Architecture
 //
-// 4 PDAs:  ProtocolConfig, Authority, AgentIdentity, Attestation
-// 12 Instructions:
-//   Admin:       initialize, add_authority, remove_authority, set_paused, transfer_admin
-//   Agent:       register_agent, flag_agent, unflag_agent, refresh_identity_signals
-//   Attestation: submit_attestation, revoke_attestation, close_attestation
+// 17 PDAs: ProtocolConfig, Authority, AgentIdentity, Attestation, AgentTombstone,
+//          NameRecord, AgentProfile, SessionKey, Operator, OperatorAttestation,
+//          LinkedAddress, WalletLink, Schema, Evidence, AttestationBatch,
+//          EnclaveMeasurement, AttestationChallenge
+// 73 Instructions:
+//   Admin:       initialize, add_authority, remove_authority, set_paused, transfer_admin,
+//                set_operator_inheritance, set_transfer_policy, set_liveness_window,
+//                set_expiry_crank_policy, register_schema, register_builtin_schema,
+//                set_schema_active, register_enclave_measurement,
+//                revoke_enclave_measurement, set_challenge_period, resolve_challenge,
+//                migrate_config, migrate_authority
+//   Agent:       register_agent, flag_agent, unflag_agent, refresh_identity_signals,
+//                close_agent, rename_agent, create_agent_profile, update_agent_profile,
+//                close_agent_profile,
+//                propose_agent_transfer, cancel_agent_transfer, accept_agent_transfer,
+//                heartbeat, retire_agent, ban_agent, unban_agent, set_recovery_keys,
+//                self_freeze, thaw_agent, migrate_agent
+//   Session:     create_session_key, revoke_session_key, verify_agent_signer
+//   Operator:    create_operator, link_operator, unlink_operator, attest_operator,
+//                revoke_operator_attestation, flag_operator, unflag_operator
+//   Linking:     link_evm_address, unlink_evm_address, link_wallet, unlink_wallet
+//   Attestation: submit_attestation, revoke_attestation, close_attestation,
+//                set_attestation_consent, accept_attestation, reject_attestation,
+//                migrate_attestation, renew_attestation, close_expired_attestation,
+//                submit_signed_attestation, submit_schema_attestation,
+//                attach_evidence, close_evidence, submit_attestations_batch,
+//                post_attestation_batch, claim_batch_attestation,
+//                revoke_attestation_batch, challenge_attestation, close_challenge,
+//                bind_enclave_measurement, close_revoked_batch_claim
+//
+// Agent lifecycle (AgentIdentity.status):
+//   Registered ──submit_attestation──▶ Active
+//   Registered | Active ──flag_agent──▶ Suspended ──unflag_agent──▶ Active | Registered
+//   Registered | Active ──retire_agent──▶ Retired
+//   any ──ban_agent──▶ Banned ──unban_agent──▶ Suspended, or Retired if it was
+//   Banned agents cannot close_agent, so cannot re-register to shed the ban
 // =============================================================================
 
 #[program]
@@ -27,6 +58,12 @@ pub mod moltlaunch {
         config.total_attestations = 0;
         config.paused = false;
         config.bump = ctx.bumps.config;
+        config.operator_inheritance = OperatorInheritance::None;
+        config.reset_controller_signals_on_transfer = false;
+        config.liveness_window = 0;
+        config.expiry_grace_period = 0;
+        config.cranker_tip = 0;
+        config.challenge_periods = [0; SIGNAL_TYPE_COUNT];
         Ok(())
     }
 
@@ -52,6 +89,8 @@ pub mod moltlaunch {
         authority.added_by = ctx.accounts.admin.key();
         authority.added_at = Clock::get()?.unix_timestamp;
         authority.bump = ctx.bumps.authority;
+        authority.last_signed_nonce = 0;
+        authority.open_signed_nonces = 0;
 
         emit!(AuthorityAdded {
             authority: authority.pubkey,
@@ -86,15 +125,33 @@ pub mod moltlaunch {
     }
 
     // =========================================================================
-    // 4. register_agent — anyone creates their AgentIdentity PDA
+    // 4. register_agent — anyone creates their AgentIdentity PDA and claims
+    //    a unique NameRecord for its normalized name
     // =========================================================================
     pub fn register_agent(ctx: Context<RegisterAgent>, name: String) -> Result<()> {
         let config = &mut ctx.accounts.config;
         require!(!config.paused, MoltError::ProtocolPaused);
-        require!(name.len() <= 32, MoltError::NameTooLong);
+        let name = normalize_agent_name(&name)?;
+        let now = Clock::get()?.unix_timestamp;
 
         let agent = &mut ctx.accounts.agent;
         agent.wallet = ctx.accounts.wallet.key();
+        agent.owner = ctx.accounts.wallet.key();
+        agent.pending_owner = None;
+        agent.signals_reset_at = 0;
+        agent.last_active = 0;
+        agent.heartbeat_streak = 0;
+        agent.status = AgentStatus::Registered;
+        agent.previous_status = AgentStatus::Registered;
+        agent.status_changed_at = now;
+        agent.emergency_key = None;
+        agent.guardian = None;
+        agent.pending_guardian = None;
+        agent.guardian_change_at = 0;
+        agent.is_frozen = false;
+        agent.frozen_at = 0;
+        agent.requires_consent = false;
+        agent.fraud_revocations = 0;
         agent.infra_type = InfraType::Unknown;
         agent.has_economic_stake = false;
         agent.has_hardware_binding = false;
@@ -103,9 +160,29 @@ pub mod moltlaunch {
         agent.trust_score = 0;
         agent.last_verified = 0;
         agent.nonce = config.revocation_nonce;
-        agent.registered_at = Clock::get()?.unix_timestamp;
+        agent.registered_at = now;
         agent.name = name.clone();
         agent.bump = ctx.bumps.agent;
+        agent.live_attestations = 0;
+        agent.name_changed_at = now;
+        agent.operator = None;
+        agent.payer = ctx.accounts.payer.key();
+
+        let name_record = &mut ctx.accounts.name_record;
+        name_record.agent = agent.wallet;
+        name_record.name = name.clone();
+        name_record.claimed_at = now;
+        name_record.bump = ctx.bumps.name_record;
+        name_record.payer = ctx.accounts.payer.key();
+
+        // A previously closed identity cannot shed its flag or fraud record
+        // by re-registering
+        let tombstone = &ctx.accounts.tombstone;
+        if tombstone.owner == &crate::ID && !tombstone.data_is_empty() {
+            let data = tombstone.try_borrow_data()?;
+            let record: AgentTombstone = try_deserialize_padded(&data)?;
+            record.restore(agent, now);
+        }
 
         config.total_agents = config.total_agents.checked_add(1).unwrap();
 
@@ -118,14 +195,18 @@ pub mod moltlaunch {
     }
 
     // =========================================================================
-    // 5. submit_attestation — authority attests a signal for an agent
+    // 5. submit_attestation — authority attests a signal for an agent.
+    //    `index` distinguishes several attestations from the same authority;
+    //    index 0 is the original one-per-pair address. Passing `previous`
+    //    supersedes an earlier attestation of the same signal.
     // =========================================================================
     pub fn submit_attestation(
         ctx: Context<SubmitAttestation>,
         signal_type: SignalType,
         attestation_hash: [u8; 32],
-        tee_quote: Option<[u8; 32]>,
+        tee_quote: Option<TeeQuote>,
         expires_at: i64,
+        index: u8,
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
         require!(!config.paused, MoltError::ProtocolPaused);
@@ -135,11 +216,14 @@ pub mod moltlaunch {
 
         let agent = &mut ctx.accounts.agent;
         require!(!agent.is_flagged, MoltError::AgentFlagged);
+        require!(agent.status.is_in_service(), MoltError::InvalidAgentStatus);
 
         let now = Clock::get()?.unix_timestamp;
 
         // Expiry must be in the future
         require!(expires_at > now, MoltError::AttestationExpired);
+        let (enclave_measurement, tee_quote) =
+            check_tee_quote(&signal_type, &tee_quote, ctx.accounts.enclave.as_ref())?;
 
         // Create the attestation
         let attestation = &mut ctx.accounts.attestation;
@@ -153,29 +237,22 @@ pub mod moltlaunch {
         attestation.expires_at = expires_at;
         attestation.revoked = false;
         attestation.bump = ctx.bumps.attestation;
-
-        // Update the agent's signal flags — upgrade only, never downgrade
-        match signal_type {
-            SignalType::InfraCloud => {
-                if agent.infra_type == InfraType::Unknown {
-                    agent.infra_type = InfraType::Cloud;
-                }
-            }
-            SignalType::InfraTEE => {
-                if agent.infra_type == InfraType::Unknown || agent.infra_type == InfraType::Cloud {
-                    agent.infra_type = InfraType::TEE;
-                }
-            }
-            SignalType::InfraDePIN => {
-                agent.infra_type = InfraType::DePIN; // DePIN is always highest
-            }
-            SignalType::EconomicStake => agent.has_economic_stake = true,
-            SignalType::HardwareBinding => agent.has_hardware_binding = true,
-            SignalType::General => {}
+        attestation.pending = agent.requires_consent;
+        attestation.finalizes_at = config.finalizes_at(&attestation.signal_contributed, now);
+        attestation.index = index;
+        attestation.payer = ctx.accounts.payer.key();
+        attestation.schema_id = signal_type.schema_id();
+        attestation.enclave_measurement = enclave_measurement;
+
+        // Consent-mode acceptance and any challenge window gate the signal
+        if attestation.is_effective(now) {
+            agent.record_attestation(&signal_type, now);
         }
+        agent.live_attestations = agent.live_attestations.checked_add(1).unwrap();
 
-        agent.attestation_count = agent.attestation_count.checked_add(1).unwrap();
-        agent.last_verified = now;
+        if let Some(previous) = ctx.accounts.previous.as_mut() {
+            supersede_attestation(attestation, previous, agent, now)?;
+        }
 
         authority_account.attestation_count = authority_account
             .attestation_count
@@ -188,15 +265,21 @@ pub mod moltlaunch {
             agent: agent.wallet,
             authority: ctx.accounts.authority_signer.key(),
             signal_type,
+            pending: attestation.pending,
         });
 
         Ok(())
     }
 
     // =========================================================================
-    // 6. revoke_attestation — authority revokes their own attestation
+    // 6. revoke_attestation — authority revokes their own attestation, with
+    //    a reason. Fraud revocations count against the agent's score.
     // =========================================================================
-    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+    pub fn revoke_attestation(
+        ctx: Context<RevokeAttestation>,
+        reason: RevocationReason,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
         let config = &mut ctx.accounts.config;
         require!(!config.paused, MoltError::ProtocolPaused);
 
@@ -207,14 +290,28 @@ pub mod moltlaunch {
             attestation.authority == ctx.accounts.authority_signer.key(),
             MoltError::Unauthorized
         );
+        require!(!attestation.revoked, MoltError::AttestationRevoked);
 
+        let now = Clock::get()?.unix_timestamp;
         attestation.revoked = true;
+        attestation.revoked_at = now;
+        attestation.revocation_reason = reason.clone();
+        attestation.revocation_reason_hash = reason_hash;
+
+        let agent = &mut ctx.accounts.agent;
+        agent.live_attestations = agent.live_attestations.saturating_sub(1);
+        if reason == RevocationReason::Fraud {
+            agent.fraud_revocations = agent.fraud_revocations.saturating_add(1);
+        }
 
         config.revocation_nonce = config.revocation_nonce.checked_add(1).unwrap();
 
         emit!(AttestationRevoked {
             agent: attestation.agent,
             authority: attestation.authority,
+            reason,
+            reason_hash,
+            revoked_at: now,
         });
 
         Ok(())
@@ -236,6 +333,9 @@ pub mod moltlaunch {
         let agent = &mut ctx.accounts.agent;
         agent.is_flagged = true;
         agent.trust_score = 0;
+        if agent.status.is_in_service() {
+            agent.transition(AgentStatus::Suspended, Clock::get()?.unix_timestamp);
+        }
 
         emit!(AgentFlagged {
             agent: agent.wallet,
@@ -258,7 +358,16 @@ pub mod moltlaunch {
         );
 
         let agent = &mut ctx.accounts.agent;
+        require!(agent.status != AgentStatus::Banned, MoltError::InvalidAgentStatus);
         agent.is_flagged = false;
+        if agent.status == AgentStatus::Suspended {
+            let next = if agent.live_attestations > 0 {
+                AgentStatus::Active
+            } else {
+                AgentStatus::Registered
+            };
+            agent.transition(next, Clock::get()?.unix_timestamp);
+        }
 
         emit!(AgentUnflagged {
             agent: agent.wallet,
@@ -270,6 +379,8 @@ pub mod moltlaunch {
     // =========================================================================
     // 9. refresh_identity_signals — PERMISSIONLESS trust score recalculation
     //    Reads attestation PDAs from remaining_accounts, resets & rebuilds.
+    //    Agents linked to an operator must also pass the Operator PDA, plus
+    //    any OperatorAttestation PDAs to inherit under the config policy.
     // =========================================================================
     pub fn refresh_identity_signals<'info>(
         ctx: Context<'_, '_, 'info, 'info, RefreshIdentitySignals<'info>>,
@@ -287,6 +398,28 @@ pub mod moltlaunch {
 
         let now = Clock::get()?.unix_timestamp;
         let program_id = crate::ID;
+        let mut operator_seen = false;
+        let mut operator_flagged = false;
+        // Highest weight seen per custom schema, so duplicates do not stack
+        let mut schema_weights: Vec<(u32, u8)> = Vec::new();
+
+        // Batches and enclave measurements passed alongside the attestations
+        // that depend on them, with whether each still vouches for them
+        let mut batches: Vec<(Pubkey, bool)> = Vec::new();
+        let mut enclaves: Vec<(Pubkey, bool)> = Vec::new();
+        for account_info in ctx.remaining_accounts.iter() {
+            if account_info.owner != &program_id {
+                continue;
+            }
+            let data = account_info.try_borrow_data()?;
+            if data.len() >= 8 && &data[..8] == AttestationBatch::DISCRIMINATOR {
+                let batch = AttestationBatch::try_deserialize(&mut &data[..])?;
+                batches.push((account_info.key(), !batch.revoked && batch.expires_at >= now));
+            } else if data.len() >= 8 && &data[..8] == EnclaveMeasurement::DISCRIMINATOR {
+                let enclave = EnclaveMeasurement::try_deserialize(&mut &data[..])?;
+                enclaves.push((account_info.key(), enclave.active));
+            }
+        }
 
         // Rebuild from remaining accounts (attestation PDAs)
         for account_info in ctx.remaining_accounts.iter() {
@@ -296,78 +429,129 @@ pub mod moltlaunch {
             }
 
             let data = account_info.try_borrow_data()?;
-            // Minimum size: 8 (discriminator) + Attestation fields
-            if data.len() < 8 + Attestation::INIT_SPACE {
+
+            // Operator-level state, for agents linked to an operator
+            if data.len() >= 8 && &data[..8] == Operator::DISCRIMINATOR {
+                let operator = Operator::try_deserialize(&mut &data[..])?;
+                if agent.operator == Some(operator.authority) {
+                    operator_seen = true;
+                    operator_flagged = operator.is_flagged;
+                }
+                continue;
+            }
+            if data.len() >= 8 && &data[..8] == OperatorAttestation::DISCRIMINATOR {
+                let attestation = OperatorAttestation::try_deserialize(&mut &data[..])?;
+                if agent.operator != Some(attestation.operator)
+                    || attestation.expires_at < now
+                    || config.operator_inheritance == OperatorInheritance::None
+                {
+                    continue;
+                }
+                if config.operator_inheritance == OperatorInheritance::Full {
+                    agent.apply_signal(&attestation.signal_contributed);
+                }
+                agent.attestation_count = agent.attestation_count.saturating_add(1);
                 continue;
             }
 
             // Check discriminator matches Attestation
-            let disc = &data[..8];
-            if disc != Attestation::DISCRIMINATOR {
+            if data.len() < 8 || &data[..8] != Attestation::DISCRIMINATOR {
                 continue;
             }
 
-            // Deserialize the account data (skip 8-byte discriminator)
-            let attestation: Attestation =
-                Attestation::try_deserialize(&mut &data[..]).map_err(|_| MoltError::InvalidSignalType)?;
+            // Deserialize, tolerating attestations written by older layouts
+            let attestation = Attestation::try_deserialize_padded(&data)
+                .map_err(|_| MoltError::InvalidSignalType)?;
 
             // Skip if not for this agent
             if attestation.agent != agent.wallet {
                 continue;
             }
-            // Skip revoked
-            if attestation.revoked {
+            // Skip revoked, pending ones the agent has not accepted, and ones
+            // still in (or held by a challenge to) their challenge window
+            if !attestation.is_effective(now) {
                 continue;
             }
             // Skip expired
             if attestation.expires_at > 0 && attestation.expires_at < now {
                 continue;
             }
+            // Skip batch claims unless their batch is passed and still live
+            if attestation.batch != Pubkey::default()
+                && !batches.contains(&(attestation.batch, true))
+            {
+                continue;
+            }
+            // Skip TEE signals unless their enclave measurement is passed and active
+            if attestation.signal_contributed == SignalType::InfraTEE
+                && !enclaves.contains(&(attestation.enclave_measurement, true))
+            {
+                continue;
+            }
+            // Skip controller-bound signals that predate an ownership transfer
+            let verified_at = attestation.verified_at();
+            if attestation.signal_contributed.is_controller_bound()
+                && verified_at <= agent.signals_reset_at
+            {
+                continue;
+            }
 
-            // Apply signal
-            match attestation.signal_contributed {
-                SignalType::InfraCloud => {
-                    if (agent.infra_type.clone() as u8) < (InfraType::Cloud as u8) {
-                        agent.infra_type = InfraType::Cloud;
-                    }
-                }
-                SignalType::InfraTEE => {
-                    if (agent.infra_type.clone() as u8) < (InfraType::TEE as u8) {
-                        agent.infra_type = InfraType::TEE;
-                    }
+            // Apply signal; custom schemas score through their weight instead
+            if attestation.schema_id >= FIRST_CUSTOM_SCHEMA_ID {
+                match schema_weights.iter_mut().find(|(id, _)| *id == attestation.schema_id) {
+                    Some((_, weight)) => *weight = (*weight).max(attestation.schema_weight),
+                    None => schema_weights.push((attestation.schema_id, attestation.schema_weight)),
                 }
-                SignalType::InfraDePIN => {
-                    agent.infra_type = InfraType::DePIN; // highest
-                }
-                SignalType::EconomicStake => agent.has_economic_stake = true,
-                SignalType::HardwareBinding => agent.has_hardware_binding = true,
-                SignalType::General => {}
+            } else {
+                agent.apply_signal(&attestation.signal_contributed);
             }
 
             agent.attestation_count = agent.attestation_count.saturating_add(1);
-            if attestation.created_at > agent.last_verified {
-                agent.last_verified = attestation.created_at;
+            if verified_at > agent.last_verified {
+                agent.last_verified = verified_at;
             }
         }
 
+        // A linked agent cannot dodge an operator flag by omitting the operator
+        if agent.operator.is_some() {
+            require!(operator_seen, MoltError::OperatorAccountMissing);
+        }
+
+        // Attestations that finalized after a challenge window activate here
+        if agent.attestation_count >= 1 && agent.status == AgentStatus::Registered {
+            agent.transition(AgentStatus::Active, now);
+        }
+
         // Derive trust score
         let mut score: u8 = 0;
         if agent.attestation_count >= 1 {
             score = score.saturating_add(20);
         }
         match agent.infra_type {
-            InfraType::Cloud => score = score.saturating_add(10),
-            InfraType::TEE => score = score.saturating_add(25),
-            InfraType::DePIN => score = score.saturating_add(35),
+            InfraType::Cloud => score = score.saturating_add(SignalType::InfraCloud.weight()),
+            InfraType::TEE => score = score.saturating_add(SignalType::InfraTEE.weight()),
+            InfraType::DePIN => score = score.saturating_add(SignalType::InfraDePIN.weight()),
             InfraType::Unknown => {}
         }
         if agent.has_economic_stake {
-            score = score.saturating_add(25);
+            score = score.saturating_add(SignalType::EconomicStake.weight());
         }
         if agent.has_hardware_binding {
-            score = score.saturating_add(20);
+            score = score.saturating_add(SignalType::HardwareBinding.weight());
+        }
+        for (_, weight) in &schema_weights {
+            score = score.saturating_add(*weight);
+        }
+        let fraud_revocations = u8::try_from(agent.fraud_revocations).unwrap_or(u8::MAX);
+        score = score.saturating_sub(
+            fraud_revocations.saturating_mul(AgentIdentity::FRAUD_REVOCATION_PENALTY),
+        );
+        if config.liveness_window > 0
+            && now - agent.last_active.max(agent.registered_at) > config.liveness_window
+        {
+            score = score.saturating_sub(AgentIdentity::LIVENESS_PENALTY);
         }
-        if agent.is_flagged {
+        if agent.is_flagged || agent.is_frozen || operator_flagged || !agent.status.is_in_service() {
             score = 0;
         }
 
@@ -393,10 +577,13 @@ pub mod moltlaunch {
             attestation.authority == ctx.accounts.authority_signer.key(),
             MoltError::Unauthorized
         );
-        // Account is closed via close = authority_signer in the Accounts struct
+        // Account is closed via close = rent_recipient in the Accounts struct
         emit!(AttestationRevoked {
             agent: attestation.agent,
             authority: attestation.authority,
+            reason: attestation.revocation_reason.clone(),
+            reason_hash: attestation.revocation_reason_hash,
+            revoked_at: attestation.revoked_at,
         });
         Ok(())
     }
@@ -426,355 +613,5405 @@ pub mod moltlaunch {
         config.admin = new_admin;
         Ok(())
     }
-}
 
-// =============================================================================
-// Account Contexts
-// =============================================================================
+    // =========================================================================
+    // 13. close_agent — wallet deregisters its identity, reclaims rent.
+    //     Leaves a tombstone so a flag survives re-registration; banned
+    //     agents cannot close.
+    // =========================================================================
+    pub fn close_agent(ctx: Context<CloseAgent>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        config.total_agents = config.total_agents.checked_sub(1).unwrap();
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + ProtocolConfig::INIT_SPACE,
-        seeds = [b"moltlaunch"],
-        bump
-    )]
-    pub config: Account<'info, ProtocolConfig>,
+        let agent = &ctx.accounts.agent;
+        require!(
+            agent.live_attestations == 0,
+            MoltError::AgentHasLiveAttestations
+        );
+        require!(agent.operator.is_none(), MoltError::AgentLinkedToOperator);
+        // A leftover profile would reattach to whoever registers this wallet next
+        require!(
+            ctx.accounts.profile.data_is_empty(),
+            MoltError::AgentHasProfile
+        );
+        // Closing would let the wallet re-register without the ban
+        require!(agent.status != AgentStatus::Banned, MoltError::InvalidAgentStatus);
+
+        let tombstone = &mut ctx.accounts.tombstone;
+        tombstone.wallet = agent.wallet;
+        tombstone.was_flagged = tombstone.was_flagged || agent.is_flagged;
+        tombstone.fraud_revocations = tombstone.fraud_revocations.max(agent.fraud_revocations);
+        tombstone.closed_at = Clock::get()?.unix_timestamp;
+        tombstone.close_count = tombstone.close_count.saturating_add(1);
+        tombstone.bump = ctx.bumps.tombstone;
+
+        // Agent and NameRecord are closed via close = rent_recipient and
+        // close = name_rent_recipient in the Accounts struct
+        emit!(AgentClosed {
+            wallet: agent.wallet,
+            was_flagged: tombstone.was_flagged,
+        });
 
-    #[account(mut)]
-    pub admin: Signer<'info>,
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    // =========================================================================
+    // 14. rename_agent — wallet claims a new name, releasing the old one.
+    //     Rate-limited by RENAME_COOLDOWN.
+    // =========================================================================
+    pub fn rename_agent(ctx: Context<RenameAgent>, new_name: String) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        let new_name = normalize_agent_name(&new_name)?;
 
-#[derive(Accounts)]
-pub struct AddAuthority<'info> {
-    #[account(
-        seeds = [b"moltlaunch"],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, ProtocolConfig>,
+        let now = Clock::get()?.unix_timestamp;
+        let agent = &mut ctx.accounts.agent;
+        require!(
+            now >= agent.name_changed_at.saturating_add(RENAME_COOLDOWN),
+            MoltError::RenameCooldown
+        );
 
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + Authority::INIT_SPACE,
-        seeds = [b"authority", authority_pubkey.key().as_ref()],
-        bump
-    )]
-    pub authority: Account<'info, Authority>,
+        // Old NameRecord is closed via close = rent_recipient in the Accounts struct
+        let old_name = std::mem::replace(&mut agent.name, new_name.clone());
+        agent.name_changed_at = now;
 
-    /// CHECK: The pubkey of the authority being added (not necessarily a signer)
-    pub authority_pubkey: UncheckedAccount<'info>,
+        let name_record = &mut ctx.accounts.new_name_record;
+        name_record.agent = agent.wallet;
+        name_record.name = new_name.clone();
+        name_record.claimed_at = now;
+        name_record.bump = ctx.bumps.new_name_record;
+        name_record.payer = ctx.accounts.payer.key();
 
-    #[account(mut)]
-    pub admin: Signer<'info>,
+        emit!(AgentRenamed {
+            wallet: agent.wallet,
+            old_name,
+            new_name,
+        });
 
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct RemoveAuthority<'info> {
-    #[account(
-        mut,
-        seeds = [b"moltlaunch"],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, ProtocolConfig>,
+    // =========================================================================
+    // 15. create_agent_profile — wallet attaches descriptive metadata
+    // =========================================================================
+    pub fn create_agent_profile(
+        ctx: Context<CreateAgentProfile>,
+        data: AgentProfileData,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        data.validate()?;
+
+        let profile = &mut ctx.accounts.profile;
+        profile.agent = ctx.accounts.agent.wallet;
+        profile.version = 0;
+        profile.bump = ctx.bumps.profile;
+        profile.apply(data, Clock::get()?.unix_timestamp);
+
+        emit!(AgentProfileUpdated {
+            agent: profile.agent,
+            content_hash: profile.content_hash,
+            version: profile.version,
+        });
 
-    #[account(
-        mut,
-        seeds = [b"authority", authority.pubkey.as_ref()],
-        bump = authority.bump,
-    )]
-    pub authority: Account<'info, Authority>,
+        Ok(())
+    }
 
-    pub admin: Signer<'info>,
-}
+    // =========================================================================
+    // 16. update_agent_profile — wallet (or a session key with PROFILE_UPDATE)
+    //     replaces the profile, resizing the account to fit the new contents
+    // =========================================================================
+    pub fn update_agent_profile(
+        ctx: Context<UpdateAgentProfile>,
+        data: AgentProfileData,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        authorize_agent_signer(
+            &ctx.accounts.agent,
+            &ctx.accounts.signer.key(),
+            ctx.accounts.session.as_deref(),
+            SessionKey::PROFILE_UPDATE,
+            Clock::get()?.unix_timestamp,
+        )?;
+        data.validate()?;
+
+        let profile = &mut ctx.accounts.profile;
+        profile.version = profile.version.checked_add(1).unwrap();
+        profile.apply(data, Clock::get()?.unix_timestamp);
+
+        emit!(AgentProfileUpdated {
+            agent: profile.agent,
+            content_hash: profile.content_hash,
+            version: profile.version,
+        });
 
-#[derive(Accounts)]
-pub struct RegisterAgent<'info> {
-    #[account(
-        mut,
-        seeds = [b"moltlaunch"],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, ProtocolConfig>,
+        Ok(())
+    }
 
-    #[account(
-        init,
-        payer = wallet,
-        space = 8 + AgentIdentity::INIT_SPACE,
-        seeds = [b"agent", wallet.key().as_ref()],
-        bump
-    )]
-    pub agent: Account<'info, AgentIdentity>,
+    // =========================================================================
+    // 17. create_session_key — wallet delegates a scoped, expiring key
+    // =========================================================================
+    pub fn create_session_key(
+        ctx: Context<CreateSessionKey>,
+        session_key: Pubkey,
+        allowed_actions: u8,
+        expires_at: i64,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        require!(
+            allowed_actions != 0 && allowed_actions & !SessionKey::ALL_ACTIONS == 0,
+            MoltError::InvalidSessionActions
+        );
 
-    #[account(mut)]
-    pub wallet: Signer<'info>,
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, MoltError::SessionKeyExpired);
+        require!(
+            expires_at <= now.saturating_add(SessionKey::MAX_DURATION),
+            MoltError::SessionDurationTooLong
+        );
 
-    pub system_program: Program<'info, System>,
-}
+        let session = &mut ctx.accounts.session;
+        session.agent = ctx.accounts.agent.wallet;
+        session.owner = ctx.accounts.agent.owner;
+        session.session_key = session_key;
+        session.allowed_actions = allowed_actions;
+        session.created_at = now;
+        session.expires_at = expires_at;
+        session.bump = ctx.bumps.session;
+        session.payer = ctx.accounts.payer.key();
+
+        emit!(SessionKeyCreated {
+            agent: session.agent,
+            session_key,
+            allowed_actions,
+            expires_at,
+        });
 
-#[derive(Accounts)]
-pub struct SubmitAttestation<'info> {
-    #[account(
-        mut,
-        seeds = [b"moltlaunch"],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, ProtocolConfig>,
+        Ok(())
+    }
 
-    #[account(
-        mut,
-        seeds = [b"authority", authority_signer.key().as_ref()],
-        bump = authority.bump,
-    )]
-    pub authority: Account<'info, Authority>,
+    // =========================================================================
+    // 18. revoke_session_key — wallet closes a session key; rent returns to
+    //     whoever funded it
+    // =========================================================================
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+        let session = &ctx.accounts.session;
+        // Account is closed via close = rent_recipient in the Accounts struct
+        emit!(SessionKeyRevoked {
+            agent: session.agent,
+            session_key: session.session_key,
+        });
+        Ok(())
+    }
 
-    #[account(
-        mut,
-        seeds = [b"agent", agent.wallet.as_ref()],
-        bump = agent.bump,
-    )]
-    pub agent: Account<'info, AgentIdentity>,
+    // =========================================================================
+    // 19. verify_agent_signer — read-only check that `signer` acts for the
+    //     agent: either its wallet or a live session key allowing `action`.
+    //     Integrators CPI into this before a trust-gated action.
+    // =========================================================================
+    pub fn verify_agent_signer(ctx: Context<VerifyAgentSigner>, action: u8) -> Result<()> {
+        // An empty action would be allowed by every session key
+        require!(
+            action != 0 && action & !SessionKey::ALL_ACTIONS == 0,
+            MoltError::InvalidSessionActions
+        );
+        authorize_agent_signer(
+            &ctx.accounts.agent,
+            &ctx.accounts.signer.key(),
+            ctx.accounts.session.as_deref(),
+            action,
+            Clock::get()?.unix_timestamp,
+        )
+    }
 
-    #[account(
-        init,
-        payer = authority_signer,
-        space = 8 + Attestation::INIT_SPACE,
-        seeds = [b"attestation", agent.wallet.as_ref(), authority_signer.key().as_ref()],
-        bump
-    )]
-    pub attestation: Account<'info, Attestation>,
+    // =========================================================================
+    // 20. create_operator — a team registers the Operator PDA for its fleet
+    // =========================================================================
+    pub fn create_operator(ctx: Context<CreateOperator>, name: String) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        require!(name.len() <= 32, MoltError::NameTooLong);
 
-    #[account(mut)]
-    pub authority_signer: Signer<'info>,
+        let operator = &mut ctx.accounts.operator;
+        operator.authority = ctx.accounts.operator_authority.key();
+        operator.name = name.clone();
+        operator.agent_count = 0;
+        operator.is_flagged = false;
+        operator.created_at = Clock::get()?.unix_timestamp;
+        operator.bump = ctx.bumps.operator;
 
-    pub system_program: Program<'info, System>,
-}
+        emit!(OperatorCreated {
+            operator: operator.authority,
+            name,
+        });
 
-#[derive(Accounts)]
-pub struct RevokeAttestation<'info> {
-    #[account(
-        mut,
-        seeds = [b"moltlaunch"],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, ProtocolConfig>,
+        Ok(())
+    }
 
-    #[account(
-        mut,
-        seeds = [b"attestation", attestation.agent.as_ref(), attestation.authority.as_ref()],
-        bump = attestation.bump,
-    )]
-    pub attestation: Account<'info, Attestation>,
+    // =========================================================================
+    // 21. link_operator — agent joins a fleet; both wallets must sign
+    // =========================================================================
+    pub fn link_operator(ctx: Context<LinkOperator>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
 
-    pub authority_signer: Signer<'info>,
-}
+        let agent = &mut ctx.accounts.agent;
+        require!(agent.operator.is_none(), MoltError::AgentLinkedToOperator);
 
-#[derive(Accounts)]
-pub struct FlagAgent<'info> {
-    #[account(
-        seeds = [b"moltlaunch"],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, ProtocolConfig>,
+        let operator = &mut ctx.accounts.operator;
+        agent.operator = Some(operator.authority);
+        operator.agent_count = operator.agent_count.checked_add(1).unwrap();
 
-    #[account(
+        emit!(OperatorLinked {
+            agent: agent.wallet,
+            operator: operator.authority,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 22. unlink_operator — either the agent wallet or the operator leaves.
+    //     Not while the operator is flagged, so agents cannot shed the flag.
+    // =========================================================================
+    pub fn unlink_operator(ctx: Context<UnlinkOperator>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        let operator = &mut ctx.accounts.operator;
+        require!(
+            agent.operator == Some(operator.authority),
+            MoltError::OperatorMismatch
+        );
+        require!(!operator.is_flagged, MoltError::OperatorFlagged);
+
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == agent.owner || signer == operator.authority,
+            MoltError::Unauthorized
+        );
+
+        agent.operator = None;
+        operator.agent_count = operator.agent_count.saturating_sub(1);
+
+        emit!(OperatorUnlinked {
+            agent: agent.wallet,
+            operator: operator.authority,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 23. attest_operator — authority attests an operator (e.g. KYB)
+    // =========================================================================
+    pub fn attest_operator(
+        ctx: Context<AttestOperator>,
+        signal_type: SignalType,
+        attestation_hash: [u8; 32],
+        expires_at: i64,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let authority_account = &mut ctx.accounts.authority;
+        require!(authority_account.active, MoltError::AuthorityNotActive);
+
+        let operator = &ctx.accounts.operator;
+        require!(!operator.is_flagged, MoltError::OperatorFlagged);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, MoltError::AttestationExpired);
+
+        let attestation = &mut ctx.accounts.operator_attestation;
+        attestation.operator = operator.authority;
+        attestation.authority = ctx.accounts.authority_signer.key();
+        attestation.signal_contributed = signal_type.clone();
+        attestation.attestation_hash = attestation_hash;
+        attestation.created_at = now;
+        attestation.expires_at = expires_at;
+        attestation.bump = ctx.bumps.operator_attestation;
+        attestation.payer = ctx.accounts.payer.key();
+
+        authority_account.attestation_count = authority_account
+            .attestation_count
+            .checked_add(1)
+            .unwrap();
+
+        emit!(OperatorAttested {
+            operator: operator.authority,
+            authority: attestation.authority,
+            signal_type,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 24. revoke_operator_attestation — authority withdraws its operator
+    //     attestation; the PDA is closed outright
+    // =========================================================================
+    pub fn revoke_operator_attestation(ctx: Context<RevokeOperatorAttestation>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        config.revocation_nonce = config.revocation_nonce.checked_add(1).unwrap();
+
+        // Account is closed via close = rent_recipient in the Accounts struct
+        let attestation = &ctx.accounts.operator_attestation;
+        emit!(OperatorAttestationRevoked {
+            operator: attestation.operator,
+            authority: attestation.authority,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 25. flag_operator — an active authority flags a whole fleet
+    // =========================================================================
+    pub fn flag_operator(ctx: Context<FlagOperator>, reason_hash: [u8; 32]) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        require!(ctx.accounts.authority.active, MoltError::AuthorityNotActive);
+
+        let operator = &mut ctx.accounts.operator;
+        operator.is_flagged = true;
+
+        emit!(OperatorFlagged {
+            operator: operator.authority,
+            authority: ctx.accounts.authority_signer.key(),
+            reason_hash,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 26. unflag_operator — admin clears an operator flag
+    // =========================================================================
+    pub fn unflag_operator(ctx: Context<UnflagOperator>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+
+        let operator = &mut ctx.accounts.operator;
+        operator.is_flagged = false;
+
+        emit!(OperatorUnflagged {
+            operator: operator.authority,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 27. set_operator_inheritance — admin chooses what agents inherit from
+    //     their operator's attestations
+    // =========================================================================
+    pub fn set_operator_inheritance(
+        ctx: Context<AdminAction>,
+        policy: OperatorInheritance,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+        config.operator_inheritance = policy;
+        Ok(())
+    }
+
+    // =========================================================================
+    // 28. propose_agent_transfer — owner nominates a new controlling wallet
+    // =========================================================================
+    pub fn propose_agent_transfer(
+        ctx: Context<OwnerAction>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let agent = &mut ctx.accounts.agent;
+        require!(new_owner != agent.owner, MoltError::InvalidTransferTarget);
+        require!(agent.operator.is_none(), MoltError::AgentLinkedToOperator);
+        agent.pending_owner = Some(new_owner);
+
+        emit!(AgentTransferProposed {
+            agent: agent.wallet,
+            from: agent.owner,
+            to: new_owner,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 29. cancel_agent_transfer — owner withdraws a pending transfer
+    // =========================================================================
+    pub fn cancel_agent_transfer(ctx: Context<OwnerAction>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        require!(agent.pending_owner.is_some(), MoltError::NoPendingTransfer);
+        agent.pending_owner = None;
+        Ok(())
+    }
+
+    // =========================================================================
+    // 30. accept_agent_transfer — nominated wallet signs to take control.
+    //     The seller's recovery keys are cleared; controller-bound signals
+    //     and the trust score are reset if the config says so.
+    // =========================================================================
+    pub fn accept_agent_transfer(ctx: Context<AcceptAgentTransfer>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let agent = &mut ctx.accounts.agent;
+        require!(!agent.is_frozen, MoltError::AgentFrozen);
+        let new_owner = ctx.accounts.new_owner.key();
+        require!(
+            agent.pending_owner == Some(new_owner),
+            MoltError::NoPendingTransfer
+        );
+        require!(agent.operator.is_none(), MoltError::AgentLinkedToOperator);
+
+        let previous_owner = agent.owner;
+        agent.owner = new_owner;
+        agent.pending_owner = None;
+        // The seller's recovery keys could otherwise freeze and reclaim it
+        let recovery_keys_cleared = agent.clear_recovery_keys();
+
+        let signals_reset = config.reset_controller_signals_on_transfer;
+        if signals_reset {
+            agent.reset_controller_signals(Clock::get()?.unix_timestamp);
+        }
+
+        emit!(AgentTransferred {
+            agent: agent.wallet,
+            from: previous_owner,
+            to: new_owner,
+            signals_reset,
+            live_attestations: agent.live_attestations,
+            recovery_keys_cleared,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 31. set_transfer_policy — admin chooses whether HardwareBinding and
+    //     EconomicStake survive an ownership transfer
+    // =========================================================================
+    pub fn set_transfer_policy(
+        ctx: Context<AdminAction>,
+        reset_controller_signals: bool,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+        config.reset_controller_signals_on_transfer = reset_controller_signals;
+        Ok(())
+    }
+
+    // =========================================================================
+    // 32. heartbeat — owner or a session key with HEARTBEAT marks the agent
+    //     as alive. Consecutive beats within the liveness window extend the
+    //     streak; a gap resets it.
+    // =========================================================================
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let now = Clock::get()?.unix_timestamp;
+        authorize_agent_signer(
+            &ctx.accounts.agent,
+            &ctx.accounts.signer.key(),
+            ctx.accounts.session.as_deref(),
+            SessionKey::HEARTBEAT,
+            now,
+        )?;
+
+        let agent = &mut ctx.accounts.agent;
+        let lapsed = config.liveness_window > 0 && now - agent.last_active > config.liveness_window;
+        agent.heartbeat_streak = if agent.last_active == 0 || lapsed {
+            1
+        } else {
+            agent.heartbeat_streak.saturating_add(1)
+        };
+        agent.last_active = now;
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 33. set_liveness_window — admin sets how long an agent may go without a
+    //     heartbeat before refresh penalizes it (0 disables the penalty)
+    // =========================================================================
+    pub fn set_liveness_window(ctx: Context<AdminAction>, liveness_window: i64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+        require!(liveness_window >= 0, MoltError::InvalidLivenessWindow);
+        config.liveness_window = liveness_window;
+        Ok(())
+    }
+
+    // =========================================================================
+    // 34. retire_agent — owner permanently takes an agent out of service
+    // =========================================================================
+    pub fn retire_agent(ctx: Context<OwnerAction>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let agent = &mut ctx.accounts.agent;
+        require!(agent.status.is_in_service(), MoltError::InvalidAgentStatus);
+        agent.trust_score = 0;
+        agent.transition(AgentStatus::Retired, Clock::get()?.unix_timestamp);
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 35. ban_agent — admin bans an agent; flagged until unbanned
+    // =========================================================================
+    pub fn ban_agent(ctx: Context<AdminAgentAction>, reason_hash: [u8; 32]) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+
+        let agent = &mut ctx.accounts.agent;
+        require!(agent.status != AgentStatus::Banned, MoltError::InvalidAgentStatus);
+        agent.is_flagged = true;
+        agent.trust_score = 0;
+        agent.transition(AgentStatus::Banned, Clock::get()?.unix_timestamp);
+
+        emit!(AgentFlagged {
+            agent: agent.wallet,
+            authority: ctx.accounts.admin.key(),
+            reason_hash,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 36. unban_agent — admin lifts a ban; the agent stays Suspended and
+    //     flagged until unflag_agent, or goes back to Retired if it was
+    // =========================================================================
+    pub fn unban_agent(ctx: Context<AdminAgentAction>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+
+        let agent = &mut ctx.accounts.agent;
+        require!(agent.status == AgentStatus::Banned, MoltError::InvalidAgentStatus);
+        let next = agent.status_after_unban();
+        agent.transition(next, Clock::get()?.unix_timestamp);
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 37. set_recovery_keys — owner registers an emergency key (may freeze)
+    //     and a guardian (may thaw and recover). Not allowed while frozen.
+    //     A guardian change only takes effect after GUARDIAN_CHANGE_DELAY, and
+    //     a freeze cancels it, so a stolen owner key cannot install its own
+    //     guardian ahead of the freeze. Passing the current guardian cancels
+    //     a pending change.
+    // =========================================================================
+    pub fn set_recovery_keys(
+        ctx: Context<OwnerAction>,
+        emergency_key: Option<Pubkey>,
+        guardian: Option<Pubkey>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let now = Clock::get()?.unix_timestamp;
+        let agent = &mut ctx.accounts.agent;
+        agent.emergency_key = emergency_key;
+        agent.settle_guardian(now);
+        if guardian == agent.guardian {
+            agent.pending_guardian = None;
+            agent.guardian_change_at = 0;
+        } else {
+            agent.pending_guardian = guardian;
+            agent.guardian_change_at = now.saturating_add(GUARDIAN_CHANGE_DELAY);
+        }
+
+        emit!(RecoveryKeysUpdated {
+            agent: agent.wallet,
+            emergency_key,
+            guardian,
+            guardian_effective_at: agent.guardian_change_at,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 38. self_freeze — owner or emergency key reports a compromised key.
+    //     Blocks owner and session-key actions until thawed. Deliberately
+    //     not gated on pause: an operator must always be able to freeze.
+    // =========================================================================
+    pub fn self_freeze(ctx: Context<SelfFreeze>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == agent.owner || Some(signer) == agent.emergency_key,
+            MoltError::Unauthorized
+        );
+        require!(!agent.is_frozen, MoltError::AgentFrozen);
+
+        let now = Clock::get()?.unix_timestamp;
+        agent.is_frozen = true;
+        agent.frozen_at = now;
+        agent.trust_score = 0;
+        agent.pending_owner = None;
+        // A guardian change still pending may be the attacker's own
+        agent.settle_guardian(now);
+        agent.pending_guardian = None;
+        agent.guardian_change_at = 0;
+
+        emit!(AgentFrozen {
+            agent: agent.wallet,
+            frozen_by: signer,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 39. thaw_agent — admin at any time, or the guardian once
+    //     GUARDIAN_THAW_DELAY has passed. Either may hand control to a new
+    //     owner, which also voids every existing session key.
+    // =========================================================================
+    pub fn thaw_agent(ctx: Context<ThawAgent>, new_owner: Option<Pubkey>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let agent = &mut ctx.accounts.agent;
+        require!(agent.is_frozen, MoltError::AgentNotFrozen);
+
+        let signer = ctx.accounts.signer.key();
+        let now = Clock::get()?.unix_timestamp;
+        agent.settle_guardian(now);
+        if signer != config.admin {
+            require!(Some(signer) == agent.guardian, MoltError::Unauthorized);
+            require!(
+                now >= agent.frozen_at.saturating_add(GUARDIAN_THAW_DELAY),
+                MoltError::ThawTooEarly
+            );
+        }
+
+        let previous_owner = agent.owner;
+        if let Some(new_owner) = new_owner {
+            agent.owner = new_owner;
+        }
+        agent.is_frozen = false;
+
+        emit!(AgentThawed {
+            agent: agent.wallet,
+            thawed_by: signer,
+            previous_owner,
+            owner: agent.owner,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 40. link_evm_address — owner proves control of an EVM address with an
+    //     EIP-191 (personal_sign) signature over evm_link_message
+    // =========================================================================
+    pub fn link_evm_address(
+        ctx: Context<LinkEvmAddress>,
+        evm_address: [u8; 20],
+        signature: [u8; 64],
+        recovery_id: u8,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let agent = &ctx.accounts.agent;
+        let message = evm_link_message(&agent.wallet, &agent.owner, &evm_address);
+        let recovered = recover_evm_signer(message.as_bytes(), &signature, recovery_id)?;
+        require!(recovered == evm_address, MoltError::InvalidEvmSignature);
+
+        let linked = &mut ctx.accounts.linked_address;
+        linked.agent = agent.wallet;
+        linked.owner = agent.owner;
+        linked.evm_address = evm_address;
+        linked.linked_at = Clock::get()?.unix_timestamp;
+        linked.bump = ctx.bumps.linked_address;
+        linked.payer = ctx.accounts.payer.key();
+
+        emit!(EvmAddressLinked {
+            agent: agent.wallet,
+            evm_address,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 41. unlink_evm_address — owner removes a linked EVM address
+    // =========================================================================
+    pub fn unlink_evm_address(ctx: Context<UnlinkEvmAddress>) -> Result<()> {
+        let linked = &ctx.accounts.linked_address;
+        // Account is closed via close = rent_recipient in the Accounts struct
+        emit!(EvmAddressUnlinked {
+            agent: linked.agent,
+            evm_address: linked.evm_address,
+        });
+        Ok(())
+    }
+
+    // =========================================================================
+    // 42. link_wallet — owner and a secondary Solana key both sign to point
+    //     the secondary key at this identity
+    // =========================================================================
+    pub fn link_wallet(ctx: Context<LinkWallet>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let agent = &ctx.accounts.agent;
+        let secondary = ctx.accounts.secondary.key();
+        require!(
+            secondary != agent.wallet && secondary != agent.owner,
+            MoltError::InvalidWalletLink
+        );
+
+        let link = &mut ctx.accounts.wallet_link;
+        link.agent = agent.wallet;
+        link.owner = agent.owner;
+        link.wallet = secondary;
+        link.linked_at = Clock::get()?.unix_timestamp;
+        link.bump = ctx.bumps.wallet_link;
+        link.payer = ctx.accounts.payer.key();
+
+        emit!(WalletLinked {
+            agent: agent.wallet,
+            wallet: secondary,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 43. unlink_wallet — either the agent owner or the secondary key removes
+    //     the link. The secondary key can do so even after the agent closed.
+    // =========================================================================
+    pub fn unlink_wallet(ctx: Context<UnlinkWallet>) -> Result<()> {
+        let link = &ctx.accounts.wallet_link;
+        let signer = ctx.accounts.signer.key();
+        let is_owner = ctx
+            .accounts
+            .agent
+            .as_ref()
+            .is_some_and(|agent| agent.owner == signer);
+        require!(is_owner || signer == link.wallet, MoltError::Unauthorized);
+
+        // Account is closed via close = rent_recipient in the Accounts struct
+        emit!(WalletUnlinked {
+            agent: link.agent,
+            wallet: link.wallet,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 44. set_attestation_consent — owner opts in or out of consent mode.
+    //     In consent mode new attestations land pending; flags still apply.
+    // =========================================================================
+    pub fn set_attestation_consent(ctx: Context<OwnerAction>, required: bool) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        agent.requires_consent = required;
+        Ok(())
+    }
+
+    // =========================================================================
+    // 45. accept_attestation — owner accepts a pending attestation, applying
+    //     its signal
+    // =========================================================================
+    pub fn accept_attestation(ctx: Context<AcceptAttestation>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let attestation = &mut ctx.accounts.attestation;
+        require!(attestation.pending, MoltError::AttestationNotPending);
+        require!(!attestation.revoked, MoltError::AttestationRevoked);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(attestation.expires_at > now, MoltError::AttestationExpired);
+
+        let agent = &mut ctx.accounts.agent;
+        require!(!agent.is_flagged, MoltError::AgentFlagged);
+        require!(agent.status.is_in_service(), MoltError::InvalidAgentStatus);
+
+        attestation.pending = false;
+        if attestation.is_effective(now) {
+            agent.record_attestation(&attestation.signal_contributed, now);
+        }
+
+        emit!(AttestationAccepted {
+            agent: agent.wallet,
+            authority: attestation.authority,
+            signal_type: attestation.signal_contributed.clone(),
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 46. reject_attestation — owner declines a pending attestation; the PDA
+    //     is closed and its rent returned to whoever funded it
+    // =========================================================================
+    pub fn reject_attestation(ctx: Context<RejectAttestation>) -> Result<()> {
+        let attestation = &ctx.accounts.attestation;
+        require!(attestation.pending, MoltError::AttestationNotPending);
+
+        // Revoked attestations were already taken off the live count
+        let agent = &mut ctx.accounts.agent;
+        if !attestation.revoked {
+            agent.live_attestations = agent.live_attestations.saturating_sub(1);
+        }
+
+        // Account is closed via close = rent_recipient in the Accounts struct
+        emit!(AttestationRejected {
+            agent: agent.wallet,
+            authority: attestation.authority,
+            signal_type: attestation.signal_contributed.clone(),
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 47. migrate_attestation — PERMISSIONLESS. Grows an attestation written
+    //     by an older, shorter layout to the current size; fields added since
+    //     read as zero. Payer covers the extra rent.
+    // =========================================================================
+    pub fn migrate_attestation(ctx: Context<MigrateAttestation>) -> Result<()> {
+        let info = ctx.accounts.attestation.to_account_info();
+        let attestation = {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && &data[..8] == Attestation::DISCRIMINATOR,
+                MoltError::InvalidAttestationAccount
+            );
+            Attestation::try_deserialize_padded(&data)?
+        };
+
+        let expected = Pubkey::create_program_address(
+            &[
+                b"attestation",
+                attestation.agent.as_ref(),
+                attestation.authority.as_ref(),
+                attestation.index_seed(),
+                &[attestation.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| MoltError::InvalidAttestationAccount)?;
+        require_keys_eq!(expected, info.key(), MoltError::InvalidAttestationAccount);
+
+        let new_len = 8 + Attestation::INIT_SPACE;
+        require!(info.data_len() < new_len, MoltError::AttestationUpToDate);
+        grow_account(
+            &info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            new_len,
+        )
+    }
+
+    // =========================================================================
+    // 48. renew_attestation — authority re-verifies an agent in place, without
+    //     revoking (which would bump the revocation nonce) and recreating
+    // =========================================================================
+    pub fn renew_attestation(
+        ctx: Context<RenewAttestation>,
+        attestation_hash: [u8; 32],
+        tee_quote: Option<TeeQuote>,
+        expires_at: i64,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        require!(ctx.accounts.authority.active, MoltError::AuthorityNotActive);
+
+        let agent = &ctx.accounts.agent;
+        require!(!agent.is_flagged, MoltError::AgentFlagged);
+        require!(agent.status.is_in_service(), MoltError::InvalidAgentStatus);
+
+        let attestation = &mut ctx.accounts.attestation;
+        require!(!attestation.revoked, MoltError::AttestationRevoked);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, MoltError::AttestationExpired);
+        let (enclave_measurement, tee_quote) = check_tee_quote(
+            &attestation.signal_contributed,
+            &tee_quote,
+            ctx.accounts.enclave.as_ref(),
+        )?;
+        attestation.enclave_measurement = enclave_measurement;
+
+        attestation.previous_hash = attestation.attestation_hash;
+        attestation.attestation_hash = attestation_hash;
+        attestation.tee_quote = tee_quote;
+        attestation.expires_at = expires_at;
+        attestation.renewed_at = now;
+        attestation.renewal_count = attestation.renewal_count.saturating_add(1);
+
+        emit!(AttestationRenewed {
+            agent: attestation.agent,
+            authority: attestation.authority,
+            previous_hash: attestation.previous_hash,
+            attestation_hash,
+            expires_at,
+            renewal_count: attestation.renewal_count,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 49. close_expired_attestation — PERMISSIONLESS. Closes an attestation
+    //     once expires_at + the grace period has passed. Rent goes to the
+    //     original payer, less the configured cranker tip.
+    // =========================================================================
+    pub fn close_expired_attestation(ctx: Context<CloseExpiredAttestation>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let attestation = &ctx.accounts.attestation;
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now > attestation.expires_at.saturating_add(config.expiry_grace_period),
+            MoltError::AttestationNotExpired
+        );
+
+        // Unrevoked attestations still count toward the agent's live total
+        if !attestation.revoked {
+            let agent = ctx
+                .accounts
+                .agent
+                .as_mut()
+                .ok_or(MoltError::AgentAccountMissing)?;
+            agent.live_attestations = agent.live_attestations.saturating_sub(1);
+        }
+
+        // Tip is paid out of the attestation's rent before Anchor closes it
+        let info = attestation.to_account_info();
+        let tip = config.cranker_tip.min(info.lamports());
+        if tip > 0 {
+            **info.try_borrow_mut_lamports()? -= tip;
+            **ctx.accounts.cranker.try_borrow_mut_lamports()? += tip;
+        }
+
+        // Account is closed via close = rent_recipient in the Accounts struct
+        emit!(ExpiredAttestationClosed {
+            agent: attestation.agent,
+            authority: attestation.authority,
+            rent_recipient: ctx.accounts.rent_recipient.key(),
+            cranker: ctx.accounts.cranker.key(),
+            cranker_tip: tip,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 50. set_expiry_crank_policy — admin sets the grace period after expiry
+    //     and the lamport tip paid to whoever closes an expired attestation
+    // =========================================================================
+    pub fn set_expiry_crank_policy(
+        ctx: Context<AdminAction>,
+        grace_period: i64,
+        cranker_tip: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+        require!(
+            grace_period >= 0 && cranker_tip <= MAX_CRANKER_TIP,
+            MoltError::InvalidCrankPolicy
+        );
+        config.expiry_grace_period = grace_period;
+        config.cranker_tip = cranker_tip;
+        Ok(())
+    }
+
+    // =========================================================================
+    // 51. submit_signed_attestation — anyone relays an attestation the
+    //     authority signed off-chain. The preceding instruction must be an
+    //     Ed25519 program check of the authority's signature over
+    //     signed_attestation_message; each `nonce` is accepted once, in any
+    //     order, within SIGNED_NONCE_WINDOW of the highest relayed so far.
+    //     The signed message names any `previous` attestation superseded.
+    // =========================================================================
+    pub fn submit_signed_attestation(
+        ctx: Context<SubmitSignedAttestation>,
+        signal_type: SignalType,
+        attestation_hash: [u8; 32],
+        tee_quote: Option<TeeQuote>,
+        expires_at: i64,
+        index: u8,
+        nonce: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let authority_account = &mut ctx.accounts.authority;
+        require!(authority_account.active, MoltError::AuthorityNotActive);
+
+        let agent = &mut ctx.accounts.agent;
+        require!(!agent.is_flagged, MoltError::AgentFlagged);
+        require!(agent.status.is_in_service(), MoltError::InvalidAgentStatus);
+
+        let message = signed_attestation_message(
+            &agent.wallet,
+            &signal_type,
+            &attestation_hash,
+            &tee_quote,
+            expires_at,
+            index,
+            &ctx.accounts.previous.as_ref().map(|previous| previous.key()),
+            nonce,
+        );
+        let ed25519_ix = solana_instructions_sysvar::get_instruction_relative(
+            -1,
+            &ctx.accounts.instructions.to_account_info(),
+        )
+        .map_err(|_| MoltError::InvalidEd25519Instruction)?;
+        verify_ed25519_instruction(&ed25519_ix, &authority_account.pubkey, &message)?;
+        authority_account.consume_signed_nonce(nonce)?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        // Expiry must be in the future
+        require!(expires_at > now, MoltError::AttestationExpired);
+        let (enclave_measurement, tee_quote) =
+            check_tee_quote(&signal_type, &tee_quote, ctx.accounts.enclave.as_ref())?;
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.agent = agent.wallet;
+        attestation.authority = authority_account.pubkey;
+        attestation.authority_type = authority_account.authority_type.clone();
+        attestation.signal_contributed = signal_type.clone();
+        attestation.attestation_hash = attestation_hash;
+        attestation.tee_quote = tee_quote;
+        attestation.created_at = now;
+        attestation.expires_at = expires_at;
+        attestation.revoked = false;
+        attestation.bump = ctx.bumps.attestation;
+        attestation.pending = agent.requires_consent;
+        attestation.finalizes_at = config.finalizes_at(&attestation.signal_contributed, now);
+        attestation.index = index;
+        attestation.payer = ctx.accounts.payer.key();
+        attestation.schema_id = signal_type.schema_id();
+        attestation.relayer = ctx.accounts.relayer.key();
+        attestation.enclave_measurement = enclave_measurement;
+
+        // Consent-mode acceptance and any challenge window gate the signal
+        if attestation.is_effective(now) {
+            agent.record_attestation(&signal_type, now);
+        }
+        agent.live_attestations = agent.live_attestations.checked_add(1).unwrap();
+
+        if let Some(previous) = ctx.accounts.previous.as_mut() {
+            supersede_attestation(attestation, previous, agent, now)?;
+        }
+
+        authority_account.attestation_count = authority_account
+            .attestation_count
+            .checked_add(1)
+            .unwrap();
+
+        config.total_attestations = config.total_attestations.checked_add(1).unwrap();
+
+        emit!(SignedAttestationSubmitted {
+            agent: agent.wallet,
+            authority: authority_account.pubkey,
+            relayer: attestation.relayer,
+            signal_type,
+            nonce,
+            pending: attestation.pending,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 52. register_schema — admin registers a custom signal kind. Ids below
+    //     FIRST_CUSTOM_SCHEMA_ID are reserved for the built-in SignalTypes,
+    //     registered by register_builtin_schema.
+    // =========================================================================
+    pub fn register_schema(
+        ctx: Context<RegisterSchema>,
+        id: u32,
+        name: String,
+        layout_hash: [u8; 32],
+        default_lifetime: i64,
+        weight: u8,
+        allowed_authorities: Vec<Pubkey>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+        require!(
+            id >= FIRST_CUSTOM_SCHEMA_ID
+                && !name.is_empty()
+                && name.len() <= Schema::MAX_NAME_LEN
+                && default_lifetime >= 0
+                && allowed_authorities.len() <= Schema::MAX_ALLOWED_AUTHORITIES,
+            MoltError::InvalidSchema
+        );
+
+        let schema = &mut ctx.accounts.schema;
+        schema.id = id;
+        schema.name = name.clone();
+        schema.layout_hash = layout_hash;
+        schema.default_lifetime = default_lifetime;
+        schema.weight = weight;
+        schema.allowed_authorities = allowed_authorities;
+        schema.active = true;
+        schema.created_at = Clock::get()?.unix_timestamp;
+        schema.bump = ctx.bumps.schema;
+
+        emit!(SchemaRegistered {
+            id,
+            name,
+            layout_hash,
+            weight,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 53. set_schema_active — admin stops or resumes new attestations under a
+    //     custom schema. Existing attestations keep counting until they expire.
+    //     Built-in schemas are descriptive only; submit_attestation never
+    //     consults them, so they cannot be deactivated.
+    // =========================================================================
+    pub fn set_schema_active(ctx: Context<SetSchemaActive>, active: bool) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+        let schema = &mut ctx.accounts.schema;
+        require!(schema.id >= FIRST_CUSTOM_SCHEMA_ID, MoltError::InvalidSchema);
+        schema.active = active;
+        Ok(())
+    }
+
+    // =========================================================================
+    // 54. submit_schema_attestation — authority attests a custom schema for an
+    //     agent. expires_at = 0 uses the schema's default lifetime; the
+    //     schema's weight is snapshotted onto the attestation. Passing
+    //     `previous` supersedes an earlier attestation of the same schema.
+    // =========================================================================
+    pub fn submit_schema_attestation(
+        ctx: Context<SubmitSchemaAttestation>,
+        schema_id: u32,
+        attestation_hash: [u8; 32],
+        expires_at: i64,
+        index: u8,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let authority_account = &mut ctx.accounts.authority;
+        require!(authority_account.active, MoltError::AuthorityNotActive);
+
+        let schema = &ctx.accounts.schema;
+        // Built-in signals are attested through submit_attestation
+        require!(schema.id >= FIRST_CUSTOM_SCHEMA_ID, MoltError::InvalidSchema);
+        require!(schema.active, MoltError::SchemaInactive);
+        require!(
+            schema.allows(&authority_account.pubkey),
+            MoltError::AuthorityNotAllowedForSchema
+        );
+
+        let agent = &mut ctx.accounts.agent;
+        require!(!agent.is_flagged, MoltError::AgentFlagged);
+        require!(agent.status.is_in_service(), MoltError::InvalidAgentStatus);
+
+        let now = Clock::get()?.unix_timestamp;
+        let expires_at = if expires_at == 0 {
+            now.saturating_add(schema.default_lifetime)
+        } else {
+            expires_at
+        };
+        require!(expires_at > now, MoltError::AttestationExpired);
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.agent = agent.wallet;
+        attestation.authority = authority_account.pubkey;
+        attestation.authority_type = authority_account.authority_type.clone();
+        attestation.signal_contributed = SignalType::General;
+        attestation.attestation_hash = attestation_hash;
+        attestation.tee_quote = None;
+        attestation.created_at = now;
+        attestation.expires_at = expires_at;
+        attestation.revoked = false;
+        attestation.bump = ctx.bumps.attestation;
+        attestation.pending = agent.requires_consent;
+        attestation.finalizes_at = config.finalizes_at(&attestation.signal_contributed, now);
+        attestation.index = index;
+        attestation.payer = ctx.accounts.payer.key();
+        attestation.schema_id = schema_id;
+        attestation.schema_weight = schema.weight;
+
+        // Consent-mode acceptance and any challenge window gate the signal
+        if attestation.is_effective(now) {
+            agent.record_attestation(&SignalType::General, now);
+        }
+        agent.live_attestations = agent.live_attestations.checked_add(1).unwrap();
+
+        if let Some(previous) = ctx.accounts.previous.as_mut() {
+            supersede_attestation(attestation, previous, agent, now)?;
+        }
+
+        authority_account.attestation_count = authority_account
+            .attestation_count
+            .checked_add(1)
+            .unwrap();
+
+        config.total_attestations = config.total_attestations.checked_add(1).unwrap();
+
+        emit!(SchemaAttestationSubmitted {
+            agent: agent.wallet,
+            authority: authority_account.pubkey,
+            schema_id,
+            weight: schema.weight,
+            pending: attestation.pending,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 55. attach_evidence — authority publishes where the evidence behind an
+    //     attestation lives and how its attestation_hash was computed
+    // =========================================================================
+    pub fn attach_evidence(ctx: Context<AttachEvidence>, data: EvidenceData) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        data.validate()?;
+
+        let attestation = &ctx.accounts.attestation;
+        let evidence = &mut ctx.accounts.evidence;
+        evidence.attestation = attestation.key();
+        evidence.authority = attestation.authority;
+        evidence.content_hash = attestation.attestation_hash;
+        evidence.created_at = Clock::get()?.unix_timestamp;
+        evidence.bump = ctx.bumps.evidence;
+        evidence.payer = ctx.accounts.payer.key();
+        evidence.apply(data);
+
+        emit!(EvidenceAttached {
+            attestation: evidence.attestation,
+            authority: evidence.authority,
+            uri: evidence.uri.clone(),
+            hash_algorithm: evidence.hash_algorithm.clone(),
+            content_hash: evidence.content_hash,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 56. close_evidence — authority removes an evidence record, e.g. before
+    //     re-attaching after a renewal or once the attestation is closed
+    // =========================================================================
+    pub fn close_evidence(ctx: Context<CloseEvidence>) -> Result<()> {
+        // Account is closed via close = rent_recipient in the Accounts struct
+        emit!(EvidenceClosed {
+            attestation: ctx.accounts.evidence.attestation,
+            authority: ctx.accounts.evidence.authority,
+        });
+        Ok(())
+    }
+
+    // =========================================================================
+    // 57. submit_attestations_batch — authority attests one signal for many
+    //     agents. remaining_accounts holds (agent, attestation PDA) pairs, both
+    //     writable, matching `attestation_hashes` in order. Entries that cannot
+    //     be attested are skipped and reported in the event.
+    // =========================================================================
+    pub fn submit_attestations_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubmitAttestationsBatch<'info>>,
+        signal_type: SignalType,
+        expires_at: i64,
+        index: u8,
+        attestation_hashes: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let authority_account = &mut ctx.accounts.authority;
+        require!(authority_account.active, MoltError::AuthorityNotActive);
+
+        require!(
+            !attestation_hashes.is_empty()
+                && attestation_hashes.len() <= MAX_BATCH_ATTESTATIONS
+                && ctx.remaining_accounts.len() == attestation_hashes.len() * 2,
+            MoltError::InvalidBatch
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, MoltError::AttestationExpired);
+        // Batches carry no quote, so cannot vouch for an enclave
+        check_tee_quote(&signal_type, &None, None)?;
+
+        let authority_key = ctx.accounts.authority_signer.key();
+        let space = 8 + Attestation::INIT_SPACE;
+        let lamports = Rent::get()?.minimum_balance(space);
+        let mut created: u32 = 0;
+        let mut skipped: Vec<BatchSkip> = Vec::new();
+
+        for (position, (pair, attestation_hash)) in ctx
+            .remaining_accounts
+            .chunks(2)
+            .zip(attestation_hashes)
+            .enumerate()
+        {
+            let (agent_info, attestation_info) = (&pair[0], &pair[1]);
+            let mut skip = |reason: BatchSkipReason| {
+                skipped.push(BatchSkip {
+                    position: position as u8,
+                    agent: agent_info.key(),
+                    reason,
+                })
+            };
+
+            if !agent_info.is_writable || !attestation_info.is_writable {
+                skip(BatchSkipReason::NotWritable);
+                continue;
+            }
+            let Ok(mut agent) = Account::<AgentIdentity>::try_from(agent_info) else {
+                skip(BatchSkipReason::InvalidAgent);
+                continue;
+            };
+            if agent.is_flagged || !agent.status.is_in_service() {
+                skip(BatchSkipReason::AgentNotEligible);
+                continue;
+            }
+
+            let (expected, bump) = Pubkey::find_program_address(
+                &[
+                    b"attestation",
+                    agent.wallet.as_ref(),
+                    authority_key.as_ref(),
+                    attestation_index_seed(&index),
+                ],
+                &crate::ID,
+            );
+            if expected != attestation_info.key() {
+                skip(BatchSkipReason::AddressMismatch);
+                continue;
+            }
+            if attestation_info.owner == &crate::ID {
+                skip(BatchSkipReason::AlreadyExists);
+                continue;
+            }
+
+            let system_program = ctx.accounts.system_program.to_account_info();
+            let signer_seeds: &[&[u8]] = &[
+                b"attestation",
+                agent.wallet.as_ref(),
+                authority_key.as_ref(),
+                attestation_index_seed(&index),
+                &[bump],
+            ];
+            let current_lamports = attestation_info.lamports();
+            if current_lamports == 0 {
+                anchor_lang::system_program::create_account(
+                    CpiContext::new_with_signer(
+                        system_program,
+                        anchor_lang::system_program::CreateAccount {
+                            from: ctx.accounts.payer.to_account_info(),
+                            to: attestation_info.clone(),
+                        },
+                        &[signer_seeds],
+                    ),
+                    lamports,
+                    space as u64,
+                    &crate::ID,
+                )?;
+            } else {
+                // Someone pre-funded the address, which would make
+                // create_account fail: top up, allocate and assign instead,
+                // as Anchor's `init` does
+                let top_up = lamports.saturating_sub(current_lamports);
+                if top_up > 0 {
+                    anchor_lang::system_program::transfer(
+                        CpiContext::new(
+                            system_program.clone(),
+                            anchor_lang::system_program::Transfer {
+                                from: ctx.accounts.payer.to_account_info(),
+                                to: attestation_info.clone(),
+                            },
+                        ),
+                        top_up,
+                    )?;
+                }
+                anchor_lang::system_program::allocate(
+                    CpiContext::new_with_signer(
+                        system_program.clone(),
+                        anchor_lang::system_program::Allocate {
+                            account_to_allocate: attestation_info.clone(),
+                        },
+                        &[signer_seeds],
+                    ),
+                    space as u64,
+                )?;
+                anchor_lang::system_program::assign(
+                    CpiContext::new_with_signer(
+                        system_program,
+                        anchor_lang::system_program::Assign {
+                            account_to_assign: attestation_info.clone(),
+                        },
+                        &[signer_seeds],
+                    ),
+                    &crate::ID,
+                )?;
+            }
+
+            let attestation = Attestation {
+                agent: agent.wallet,
+                authority: authority_key,
+                authority_type: authority_account.authority_type.clone(),
+                signal_contributed: signal_type.clone(),
+                attestation_hash,
+                tee_quote: None,
+                created_at: now,
+                expires_at,
+                revoked: false,
+                bump,
+                pending: agent.requires_consent,
+                index,
+                renewal_count: 0,
+                previous_hash: [0; 32],
+                renewed_at: 0,
+                payer: ctx.accounts.payer.key(),
+                revocation_reason: RevocationReason::Unspecified,
+                revocation_reason_hash: [0; 32],
+                revoked_at: 0,
+                relayer: Pubkey::default(),
+                schema_id: signal_type.schema_id(),
+                schema_weight: 0,
+                batch: Pubkey::default(),
+                enclave_measurement: Pubkey::default(),
+                finalizes_at: config.finalizes_at(&signal_type, now),
+                open_challenges: 0,
+                supersedes: Pubkey::default(),
+                superseded_by: Pubkey::default(),
+                sequence: 0,
+            };
+            attestation.try_serialize(&mut &mut attestation_info.try_borrow_mut_data()?[..])?;
+
+            // Consent-mode acceptance and any challenge window gate the signal
+            if attestation.is_effective(now) {
+                agent.record_attestation(&signal_type, now);
+            }
+            agent.live_attestations = agent.live_attestations.checked_add(1).unwrap();
+            agent.exit(&crate::ID)?;
+
+            created += 1;
+        }
+
+        authority_account.attestation_count = authority_account
+            .attestation_count
+            .checked_add(created as u64)
+            .unwrap();
+        config.total_attestations = config.total_attestations.checked_add(created as u64).unwrap();
+
+        emit!(AttestationBatchSubmitted {
+            authority: authority_key,
+            signal_type,
+            created,
+            skipped,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 58. post_attestation_batch — authority posts a Merkle root over
+    //     batch_leaf(agent, signal, hash, expiry) leaves, one per agent.
+    //     Claims record `index` but are addressed by the batch; see
+    //     Attestation::index_seed.
+    // =========================================================================
+    pub fn post_attestation_batch(
+        ctx: Context<PostAttestationBatch>,
+        batch_id: u64,
+        merkle_root: [u8; 32],
+        leaf_count: u32,
+        expires_at: i64,
+        index: u8,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        require!(ctx.accounts.authority.active, MoltError::AuthorityNotActive);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, MoltError::AttestationExpired);
+
+        let batch = &mut ctx.accounts.batch;
+        batch.authority = ctx.accounts.authority_signer.key();
+        batch.batch_id = batch_id;
+        batch.merkle_root = merkle_root;
+        batch.leaf_count = leaf_count;
+        batch.claimed_count = 0;
+        batch.index = index;
+        batch.expires_at = expires_at;
+        batch.revoked = false;
+        batch.revoked_at = 0;
+        batch.created_at = now;
+        batch.bump = ctx.bumps.batch;
+
+        emit!(AttestationBatchPosted {
+            batch: batch.key(),
+            authority: batch.authority,
+            merkle_root,
+            leaf_count,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 59. claim_batch_attestation — PERMISSIONLESS. Proves an agent's leaf in
+    //     a batch and materializes it as an Attestation. Expires at the
+    //     earlier of the leaf and batch expiry. Passing `previous` supersedes
+    //     the authority's attestation of the same signal, if it was last
+    //     verified before the batch was posted.
+    // =========================================================================
+    pub fn claim_batch_attestation(
+        ctx: Context<ClaimBatchAttestation>,
+        signal_type: SignalType,
+        attestation_hash: [u8; 32],
+        expires_at: i64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let authority_account = &mut ctx.accounts.authority;
+        require!(authority_account.active, MoltError::AuthorityNotActive);
+
+        let batch = &mut ctx.accounts.batch;
+        require!(!batch.revoked, MoltError::AttestationRevoked);
+        require!(batch.claimed_count < batch.leaf_count, MoltError::BatchFullyClaimed);
+
+        let agent = &mut ctx.accounts.agent;
+        require!(!agent.is_flagged, MoltError::AgentFlagged);
+        require!(agent.status.is_in_service(), MoltError::InvalidAgentStatus);
+
+        // Batches carry no quote, so cannot vouch for an enclave
+        check_tee_quote(&signal_type, &None, None)?;
+
+        let leaf = batch_leaf(&agent.wallet, &signal_type, &attestation_hash, expires_at);
+        require!(
+            verify_merkle_proof(&proof, &batch.merkle_root, leaf),
+            MoltError::InvalidMerkleProof
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let expires_at = expires_at.min(batch.expires_at);
+        require!(expires_at > now, MoltError::AttestationExpired);
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.agent = agent.wallet;
+        attestation.authority = batch.authority;
+        attestation.authority_type = authority_account.authority_type.clone();
+        attestation.signal_contributed = signal_type.clone();
+        attestation.attestation_hash = attestation_hash;
+        attestation.tee_quote = None;
+        attestation.created_at = now;
+        attestation.expires_at = expires_at;
+        attestation.revoked = false;
+        attestation.bump = ctx.bumps.attestation;
+        attestation.pending = agent.requires_consent;
+        attestation.finalizes_at = config.finalizes_at(&attestation.signal_contributed, now);
+        attestation.index = batch.index;
+        attestation.payer = ctx.accounts.payer.key();
+        attestation.schema_id = signal_type.schema_id();
+        attestation.batch = batch.key();
+
+        // Consent-mode acceptance and any challenge window gate the signal
+        if attestation.is_effective(now) {
+            agent.record_attestation(&signal_type, now);
+        }
+        agent.live_attestations = agent.live_attestations.checked_add(1).unwrap();
+
+        // Anyone may claim, so a stale batch cannot replace a newer attestation
+        if let Some(previous) = ctx.accounts.previous.as_mut() {
+            require!(
+                previous.verified_at() <= batch.created_at,
+                MoltError::InvalidSupersession
+            );
+            supersede_attestation(attestation, previous, agent, now)?;
+        }
+
+        batch.claimed_count += 1;
+        authority_account.attestation_count = authority_account
+            .attestation_count
+            .checked_add(1)
+            .unwrap();
+
+        config.total_attestations = config.total_attestations.checked_add(1).unwrap();
+
+        emit!(BatchAttestationClaimed {
+            batch: batch.key(),
+            agent: agent.wallet,
+            authority: batch.authority,
+            signal_type,
+            pending: attestation.pending,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 60. revoke_attestation_batch — authority revokes every claim of a batch
+    //     at once; refresh stops counting them, no new claims are accepted and
+    //     existing claims may be closed with close_revoked_batch_claim
+    // =========================================================================
+    pub fn revoke_attestation_batch(
+        ctx: Context<RevokeAttestationBatch>,
+        reason: RevocationReason,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+
+        let batch = &mut ctx.accounts.batch;
+        require!(!batch.revoked, MoltError::AttestationRevoked);
+
+        let now = Clock::get()?.unix_timestamp;
+        batch.revoked = true;
+        batch.revoked_at = now;
+
+        config.revocation_nonce = config.revocation_nonce.checked_add(1).unwrap();
+
+        emit!(AttestationBatchRevoked {
+            batch: batch.key(),
+            authority: batch.authority,
+            reason,
+            reason_hash,
+            revoked_at: now,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 61. register_enclave_measurement — admin approves an enclave build
+    //     (MRENCLAVE / MRSIGNER / MRTD) that InfraTEE attestations may cite
+    // =========================================================================
+    pub fn register_enclave_measurement(
+        ctx: Context<RegisterEnclaveMeasurement>,
+        vendor: TeeVendor,
+        kind: MeasurementKind,
+        measurement: [u8; 32],
+        label: String,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+        require!(
+            label.len() <= EnclaveMeasurement::MAX_LABEL_LEN,
+            MoltError::NameTooLong
+        );
+
+        let enclave = &mut ctx.accounts.enclave;
+        enclave.vendor = vendor.clone();
+        enclave.kind = kind.clone();
+        enclave.measurement = measurement;
+        enclave.label = label;
+        enclave.active = true;
+        enclave.added_by = ctx.accounts.admin.key();
+        enclave.added_at = Clock::get()?.unix_timestamp;
+        enclave.revoked_at = 0;
+        enclave.bump = ctx.bumps.enclave;
+
+        emit!(EnclaveMeasurementRegistered {
+            vendor,
+            kind,
+            measurement,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 62. revoke_enclave_measurement — admin withdraws approval; InfraTEE
+    //     attestations citing it stop counting at the next refresh
+    // =========================================================================
+    pub fn revoke_enclave_measurement(ctx: Context<RevokeEnclaveMeasurement>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+
+        let enclave = &mut ctx.accounts.enclave;
+        require!(enclave.active, MoltError::EnclaveMeasurementInactive);
+        enclave.active = false;
+        enclave.revoked_at = Clock::get()?.unix_timestamp;
+
+        config.revocation_nonce = config.revocation_nonce.checked_add(1).unwrap();
+
+        emit!(EnclaveMeasurementRevoked {
+            measurement: enclave.measurement,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 63. set_challenge_period — admin sets how long new attestations of a
+    //     signal type wait before taking effect (0 = immediately)
+    // =========================================================================
+    pub fn set_challenge_period(
+        ctx: Context<AdminAction>,
+        signal_type: SignalType,
+        period: i64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+        require!(
+            (0..=MAX_CHALLENGE_PERIOD).contains(&period),
+            MoltError::InvalidChallengePeriod
+        );
+        config.challenge_periods[signal_type as usize] = period;
+        Ok(())
+    }
+
+    // =========================================================================
+    // 64. challenge_attestation — another active authority disputes an
+    //     attestation inside its challenge window, holding it until every
+    //     open challenge is resolved. One challenge per authority.
+    // =========================================================================
+    pub fn challenge_attestation(
+        ctx: Context<ChallengeAttestation>,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, MoltError::ProtocolPaused);
+        require!(ctx.accounts.authority.active, MoltError::AuthorityNotActive);
+
+        let challenger = ctx.accounts.authority_signer.key();
+        let attestation = &mut ctx.accounts.attestation;
+        require!(attestation.authority != challenger, MoltError::Unauthorized);
+        require!(!attestation.revoked, MoltError::AttestationRevoked);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(attestation.finalizes_at > now, MoltError::ChallengeWindowClosed);
+
+        attestation.open_challenges = attestation.open_challenges.checked_add(1).unwrap();
+
+        let challenge = &mut ctx.accounts.challenge;
+        challenge.attestation = attestation.key();
+        challenge.challenger = challenger;
+        challenge.evidence_hash = evidence_hash;
+        challenge.created_at = now;
+        challenge.resolved = false;
+        challenge.upheld = false;
+        challenge.resolved_at = 0;
+        challenge.bump = ctx.bumps.challenge;
+        challenge.payer = ctx.accounts.payer.key();
+        challenge.attestation_created_at = attestation.created_at;
+
+        emit!(AttestationChallenged {
+            agent: attestation.agent,
+            authority: attestation.authority,
+            challenger,
+            evidence_hash,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 65. resolve_challenge — admin rules on a challenge. Upheld revokes the
+    //     attestation; dismissed releases this challenge's hold, and the
+    //     attestation finalizes as scheduled once no challenge is open.
+    // =========================================================================
+    pub fn resolve_challenge(ctx: Context<ResolveChallenge>, upheld: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+
+        let challenge = &mut ctx.accounts.challenge;
+        require!(!challenge.resolved, MoltError::ChallengeResolved);
+        // A closed-and-recreated attestation at the same address is not the
+        // one challenged; close_challenge cleans those up instead
+        require!(
+            ctx.accounts.attestation.created_at == challenge.attestation_created_at,
+            MoltError::ChallengeStale
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        challenge.resolved = true;
+        challenge.upheld = upheld;
+        challenge.resolved_at = now;
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.open_challenges = attestation.open_challenges.saturating_sub(1);
+        if upheld && !attestation.revoked {
+            attestation.revoked = true;
+            attestation.revoked_at = now;
+            attestation.revocation_reason = RevocationReason::IssuedInError;
+            attestation.revocation_reason_hash = challenge.evidence_hash;
+
+            let agent = &mut ctx.accounts.agent;
+            agent.live_attestations = agent.live_attestations.saturating_sub(1);
+
+            config.revocation_nonce = config.revocation_nonce.checked_add(1).unwrap();
+        }
+
+        emit!(ChallengeResolved {
+            agent: attestation.agent,
+            authority: attestation.authority,
+            challenger: challenge.challenger,
+            upheld,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 66. close_challenge — PERMISSIONLESS. Returns a challenge's rent to its
+    //     payer once resolved, or once its attestation has been closed or
+    //     recreated.
+    // =========================================================================
+    pub fn close_challenge(ctx: Context<CloseChallenge>) -> Result<()> {
+        let challenge = &ctx.accounts.challenge;
+        require!(
+            challenge.resolved
+                || challenge.is_stale(&ctx.accounts.attestation.try_borrow_data()?),
+            MoltError::ChallengeOpen
+        );
+        // Account is closed via close = rent_recipient in the Accounts struct
+        Ok(())
+    }
+
+    // =========================================================================
+    // 67. bind_enclave_measurement — authority attaches an enclave measurement
+    //     to an InfraTEE attestation issued before measurements existed, which
+    //     refresh_identity_signals otherwise skips
+    // =========================================================================
+    pub fn bind_enclave_measurement(
+        ctx: Context<BindEnclaveMeasurement>,
+        tee_quote: TeeQuote,
+    ) -> Result<()> {
+        require!(!ctx.accounts.config.paused, MoltError::ProtocolPaused);
+        require!(ctx.accounts.authority.active, MoltError::AuthorityNotActive);
+
+        let attestation = &mut ctx.accounts.attestation;
+        require!(!attestation.revoked, MoltError::AttestationRevoked);
+        require!(
+            attestation.signal_contributed == SignalType::InfraTEE,
+            MoltError::InvalidSignalType
+        );
+        require!(
+            attestation.enclave_measurement == Pubkey::default(),
+            MoltError::EnclaveMeasurementBound
+        );
+
+        let (enclave_measurement, tee_quote) = check_tee_quote(
+            &attestation.signal_contributed,
+            &Some(tee_quote),
+            Some(&ctx.accounts.enclave),
+        )?;
+        attestation.enclave_measurement = enclave_measurement;
+        attestation.tee_quote = tee_quote;
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 68. migrate_config — PERMISSIONLESS. Grows a ProtocolConfig written by
+    //     an older, shorter layout to the current size; settings added since
+    //     read as zero (disabled) until the admin sets them.
+    // =========================================================================
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let info = ctx.accounts.config.to_account_info();
+        {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && &data[..8] == ProtocolConfig::DISCRIMINATOR,
+                MoltError::InvalidMigrationAccount
+            );
+        }
+
+        let new_len = 8 + ProtocolConfig::INIT_SPACE;
+        require!(info.data_len() < new_len, MoltError::AccountUpToDate);
+        grow_account(
+            &info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            new_len,
+        )
+    }
+
+    // =========================================================================
+    // 69. migrate_authority — PERMISSIONLESS. Grows an Authority written by an
+    //     older, shorter layout to the current size; fields added since read
+    //     as zero.
+    // =========================================================================
+    pub fn migrate_authority(ctx: Context<MigrateAuthority>) -> Result<()> {
+        let info = ctx.accounts.authority.to_account_info();
+        let authority: Authority = {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && &data[..8] == Authority::DISCRIMINATOR,
+                MoltError::InvalidMigrationAccount
+            );
+            try_deserialize_padded(&data)?
+        };
+
+        let expected = Pubkey::create_program_address(
+            &[b"authority", authority.pubkey.as_ref(), &[authority.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| MoltError::InvalidMigrationAccount)?;
+        require_keys_eq!(expected, info.key(), MoltError::InvalidMigrationAccount);
+
+        let new_len = 8 + Authority::INIT_SPACE;
+        require!(info.data_len() < new_len, MoltError::AccountUpToDate);
+        grow_account(
+            &info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            new_len,
+        )
+    }
+
+    // =========================================================================
+    // 70. migrate_agent — admin grows an AgentIdentity written before agent
+    //     lifecycles existed. remaining_accounts must hold every attestation
+    //     of the agent; live_attestations is backfilled from them. Claims the
+    //     NameRecord for `name`: the agent's own name, or a replacement when
+    //     that is invalid or its normalized form is already taken.
+    // =========================================================================
+    pub fn migrate_agent<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateAgent<'info>>,
+        name: String,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.key() == ctx.accounts.config.admin,
+            MoltError::Unauthorized
+        );
+        let name = normalize_agent_name(&name)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        let info = ctx.accounts.agent.to_account_info();
+        let mut agent: AgentIdentity = {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && &data[..8] == AgentIdentity::DISCRIMINATOR,
+                MoltError::InvalidMigrationAccount
+            );
+            try_deserialize_padded(&data)?
+        };
+
+        let expected = Pubkey::create_program_address(
+            &[b"agent", agent.wallet.as_ref(), &[agent.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| MoltError::InvalidMigrationAccount)?;
+        require_keys_eq!(expected, info.key(), MoltError::InvalidMigrationAccount);
+
+        let new_len = 8 + AgentIdentity::INIT_SPACE;
+        require!(info.data_len() < new_len, MoltError::AccountUpToDate);
+
+        // Count the agent's unrevoked attestations, each passed once
+        let mut seen: Vec<Pubkey> = Vec::new();
+        let mut live: u32 = 0;
+        for account_info in ctx.remaining_accounts.iter() {
+            require_keys_eq!(
+                *account_info.owner,
+                crate::ID,
+                MoltError::InvalidAttestationAccount
+            );
+            let data = account_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && &data[..8] == Attestation::DISCRIMINATOR,
+                MoltError::InvalidAttestationAccount
+            );
+            let attestation = Attestation::try_deserialize_padded(&data)?;
+            require_keys_eq!(
+                attestation.agent,
+                agent.wallet,
+                MoltError::InvalidAttestationAccount
+            );
+            require!(
+                !seen.contains(account_info.key),
+                MoltError::InvalidAttestationAccount
+            );
+            seen.push(account_info.key());
+            if !attestation.revoked {
+                live = live.checked_add(1).unwrap();
+            }
+        }
+
+        let old_name = std::mem::replace(&mut agent.name, name.clone());
+        agent.live_attestations = live;
+        agent.name_changed_at = now;
+        agent.owner = agent.wallet;
+        let status = if agent.is_flagged {
+            AgentStatus::Suspended
+        } else if live > 0 {
+            AgentStatus::Active
+        } else {
+            AgentStatus::Registered
+        };
+        agent.transition(status, now);
+
+        let name_record = &mut ctx.accounts.name_record;
+        name_record.agent = agent.wallet;
+        name_record.name = name.clone();
+        name_record.claimed_at = now;
+        name_record.bump = ctx.bumps.name_record;
+        name_record.payer = ctx.accounts.payer.key();
+
+        grow_account(
+            &info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            new_len,
+        )?;
+        agent.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        if old_name != name {
+            emit!(AgentRenamed {
+                wallet: agent.wallet,
+                old_name,
+                new_name: name,
+            });
+        }
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 71. close_revoked_batch_claim — PERMISSIONLESS. Closes an attestation
+    //     claimed from a since-revoked batch, releasing it from the agent's
+    //     live total and returning its rent to the original payer.
+    // =========================================================================
+    pub fn close_revoked_batch_claim(ctx: Context<CloseRevokedBatchClaim>) -> Result<()> {
+        require!(ctx.accounts.batch.revoked, MoltError::AttestationNotRevoked);
+
+        let attestation = &ctx.accounts.attestation;
+        if !attestation.revoked {
+            let agent = &mut ctx.accounts.agent;
+            agent.live_attestations = agent.live_attestations.saturating_sub(1);
+        }
+
+        // Account is closed via close = rent_recipient in the Accounts struct
+        emit!(RevokedBatchClaimClosed {
+            batch: attestation.batch,
+            agent: attestation.agent,
+            authority: attestation.authority,
+            rent_recipient: ctx.accounts.rent_recipient.key(),
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 72. register_builtin_schema — admin bootstrap creating the Schema account
+    //     of a built-in SignalType at its reserved id, with the weight refresh
+    //     scores it at. Built-ins carry explicit expiries, so no lifetime.
+    // =========================================================================
+    pub fn register_builtin_schema(
+        ctx: Context<RegisterBuiltinSchema>,
+        signal_type: SignalType,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            MoltError::Unauthorized
+        );
+
+        let schema = &mut ctx.accounts.schema;
+        schema.id = signal_type.schema_id();
+        schema.name = signal_type.name().to_string();
+        schema.layout_hash = [0; 32];
+        schema.default_lifetime = 0;
+        schema.weight = signal_type.weight();
+        schema.allowed_authorities = Vec::new();
+        schema.active = true;
+        schema.created_at = Clock::get()?.unix_timestamp;
+        schema.bump = ctx.bumps.schema;
+
+        emit!(SchemaRegistered {
+            id: schema.id,
+            name: schema.name.clone(),
+            layout_hash: schema.layout_hash,
+            weight: schema.weight,
+        });
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // 73. close_agent_profile — wallet deletes the agent's profile, returning
+    //     its rent. Required before close_agent.
+    // =========================================================================
+    pub fn close_agent_profile(ctx: Context<CloseAgentProfile>) -> Result<()> {
+        // Account is closed via close = wallet in the Accounts struct
+        emit!(AgentProfileClosed {
+            agent: ctx.accounts.profile.agent,
+            version: ctx.accounts.profile.version,
+        });
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Account Contexts
+// =============================================================================
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ProtocolConfig::INIT_SPACE,
+        seeds = [b"moltlaunch"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddAuthority<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Authority::INIT_SPACE,
+        seeds = [b"authority", authority_pubkey.key().as_ref()],
+        bump
+    )]
+    pub authority: Account<'info, Authority>,
+
+    /// CHECK: The pubkey of the authority being added (not necessarily a signer)
+    pub authority_pubkey: UncheckedAccount<'info>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", authority.pubkey.as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct RegisterAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AgentIdentity::INIT_SPACE,
+        seeds = [b"agent", wallet.key().as_ref()],
+        bump
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + NameRecord::INIT_SPACE,
+        seeds = [b"name".as_ref(), name_seed(&name).as_ref()],
+        bump
+    )]
+    pub name_record: Account<'info, NameRecord>,
+
+    /// CHECK: Tombstone left by a previous close_agent, if any. Read manually.
+    #[account(
+        seeds = [b"tombstone", wallet.key().as_ref()],
+        bump
+    )]
+    pub tombstone: UncheckedAccount<'info>,
+
+    pub wallet: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    signal_type: SignalType,
+    attestation_hash: [u8; 32],
+    tee_quote: Option<TeeQuote>,
+    expires_at: i64,
+    index: u8,
+)]
+pub struct SubmitAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", authority_signer.key().as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Attestation::INIT_SPACE,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            authority_signer.key().as_ref(),
+            attestation_index_seed(&index),
+        ],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// Required for InfraTEE: the measurement the quote was produced under
+    #[account(
+        seeds = [
+            b"enclave",
+            enclave_seed(&enclave.vendor, &enclave.kind).as_ref(),
+            enclave.measurement.as_ref(),
+        ],
+        bump = enclave.bump,
+    )]
+    pub enclave: Option<Account<'info, EnclaveMeasurement>>,
+
+    /// This authority's earlier attestation of the agent being superseded
+    #[account(
+        mut,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            authority_signer.key().as_ref(),
+            previous.index_seed(),
+        ],
+        bump = previous.bump,
+    )]
+    pub previous: Option<Account<'info, Attestation>>,
+
+    pub authority_signer: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"attestation",
+            attestation.agent.as_ref(),
+            attestation.authority.as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", attestation.agent.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    pub authority_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FlagAgent<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"authority", authority_signer.key().as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    pub authority_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnflagAgent<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminAgentAction<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshIdentitySignals<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAttestation<'info> {
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [
+            b"attestation",
+            attestation.agent.as_ref(),
+            attestation.authority.as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// CHECK: Receives the rent; whoever funded the attestation
+    #[account(mut, address = attestation.rent_recipient())]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub authority_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        mut,
+        close = name_rent_recipient,
+        seeds = [b"name".as_ref(), name_seed(&agent.name).as_ref()],
+        bump = name_record.bump,
+    )]
+    pub name_record: Account<'info, NameRecord>,
+
+    /// CHECK: The agent's profile PDA; must already be closed
+    #[account(
+        seeds = [b"profile", agent.wallet.as_ref()],
+        bump,
+    )]
+    pub profile: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AgentTombstone::INIT_SPACE,
+        seeds = [b"tombstone", agent.wallet.as_ref()],
+        bump
+    )]
+    pub tombstone: Account<'info, AgentTombstone>,
+
+    /// CHECK: Receives the agent's rent; whoever funded the agent
+    #[account(mut, address = agent.rent_recipient())]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the name record's rent; whoever funded the name record
+    #[account(mut, address = name_record.payer)]
+    pub name_rent_recipient: UncheckedAccount<'info>,
+
+    pub wallet: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_name: String)]
+pub struct RenameAgent<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [b"name".as_ref(), name_seed(&agent.name).as_ref()],
+        bump = old_name_record.bump,
+    )]
+    pub old_name_record: Account<'info, NameRecord>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + NameRecord::INIT_SPACE,
+        seeds = [b"name".as_ref(), name_seed(&new_name).as_ref()],
+        bump
+    )]
+    pub new_name_record: Account<'info, NameRecord>,
+
+    /// CHECK: Receives the old name record's rent; whoever funded it
+    #[account(mut, address = old_name_record.payer)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub wallet: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(data: AgentProfileData)]
+pub struct CreateAgentProfile<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = AgentProfile::space(&data),
+        seeds = [b"profile", agent.wallet.as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, AgentProfile>,
+
+    pub wallet: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(data: AgentProfileData)]
+pub struct UpdateAgentProfile<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        mut,
+        realloc = AgentProfile::space(&data),
+        realloc::payer = payer,
+        realloc::zero = false,
+        seeds = [b"profile", agent.wallet.as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, AgentProfile>,
+
+    /// Required when `signer` is a session key rather than the agent wallet
+    #[account(
+        seeds = [b"session", agent.wallet.as_ref(), signer.key().as_ref()],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+
+    /// Agent owner or session key
+    pub signer: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAgentProfile<'info> {
+    #[account(
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [b"profile", agent.wallet.as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, AgentProfile>,
+
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSessionKey<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SessionKey::INIT_SPACE,
+        seeds = [b"session", agent.wallet.as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+
+    pub wallet: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSessionKey<'info> {
+    #[account(
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [b"session", agent.wallet.as_ref(), session.session_key.as_ref()],
+        bump = session.bump,
+    )]
+    pub session: Account<'info, SessionKey>,
+
+    /// CHECK: Receives the rent; whoever funded the session key
+    #[account(mut, address = session.payer)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub wallet: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyAgentSigner<'info> {
+    #[account(
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    /// Required when `signer` is a session key rather than the agent wallet
+    #[account(
+        seeds = [b"session", agent.wallet.as_ref(), signer.key().as_ref()],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateOperator<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Operator::INIT_SPACE,
+        seeds = [b"operator", operator_authority.key().as_ref()],
+        bump
+    )]
+    pub operator: Account<'info, Operator>,
+
+    pub operator_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LinkOperator<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"operator", operator_authority.key().as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    pub operator_authority: Signer<'info>,
+
+    pub wallet: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnlinkOperator<'info> {
+    #[account(
+        mut,
+        seeds = [b"operator", operator.authority.as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    /// Agent wallet or operator authority
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestOperator<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", authority_signer.key().as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    #[account(
+        seeds = [b"operator", operator.authority.as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Account<'info, Operator>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + OperatorAttestation::INIT_SPACE,
+        seeds = [b"operator_attestation", operator.authority.as_ref(), authority_signer.key().as_ref()],
+        bump
+    )]
+    pub operator_attestation: Account<'info, OperatorAttestation>,
+
+    pub authority_signer: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeOperatorAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [b"operator_attestation", operator_attestation.operator.as_ref(), authority_signer.key().as_ref()],
+        bump = operator_attestation.bump,
+    )]
+    pub operator_attestation: Account<'info, OperatorAttestation>,
+
+    /// CHECK: Receives the rent; whoever funded the operator attestation
+    #[account(mut, address = operator_attestation.payer)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub authority_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FlagOperator<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"authority", authority_signer.key().as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    #[account(
+        mut,
+        seeds = [b"operator", operator.authority.as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Account<'info, Operator>,
+
+    pub authority_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnflagOperator<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"operator", operator.authority.as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Account<'info, Operator>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAgentTransfer<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    pub new_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    /// Required when `signer` is a session key rather than the agent owner
+    #[account(
+        seeds = [b"session", agent.wallet.as_ref(), signer.key().as_ref()],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+
+    /// Agent owner or session key
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OwnerAction<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    pub wallet: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SelfFreeze<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    /// Agent owner or emergency key
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ThawAgent<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    /// Admin or guardian
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(evm_address: [u8; 20])]
+pub struct LinkEvmAddress<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + LinkedAddress::INIT_SPACE,
+        seeds = [b"evm", agent.wallet.as_ref(), evm_address.as_ref()],
+        bump
+    )]
+    pub linked_address: Account<'info, LinkedAddress>,
+
+    pub wallet: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlinkEvmAddress<'info> {
+    #[account(
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [b"evm", agent.wallet.as_ref(), linked_address.evm_address.as_ref()],
+        bump = linked_address.bump,
+    )]
+    pub linked_address: Account<'info, LinkedAddress>,
+
+    /// CHECK: Receives the rent; whoever funded the link
+    #[account(mut, address = linked_address.payer)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub wallet: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LinkWallet<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + WalletLink::INIT_SPACE,
+        seeds = [b"wallet_link", secondary.key().as_ref()],
+        bump
+    )]
+    pub wallet_link: Account<'info, WalletLink>,
+
+    pub wallet: Signer<'info>,
+
+    /// The key being linked; must consent by signing
+    pub secondary: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlinkWallet<'info> {
+    /// Required when the agent owner is the signer
+    #[account(
+        seeds = [b"agent", wallet_link.agent.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Option<Account<'info, AgentIdentity>>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [b"wallet_link", wallet_link.wallet.as_ref()],
+        bump = wallet_link.bump,
+    )]
+    pub wallet_link: Account<'info, WalletLink>,
+
+    /// CHECK: Receives the rent; whoever funded the link
+    #[account(mut, address = wallet_link.payer)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    /// Agent owner or the linked secondary key
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAttestation<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            attestation.authority.as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    pub wallet: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RejectAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+        constraint = agent.owner == wallet.key() @ MoltError::Unauthorized,
+        constraint = !agent.is_frozen @ MoltError::AgentFrozen,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            attestation.authority.as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// CHECK: Receives the rent; whoever funded the attestation
+    #[account(mut, address = attestation.rent_recipient())]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub wallet: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAttestation<'info> {
+    /// CHECK: Legacy-layout attestation; discriminator and PDA verified manually
+    #[account(mut, owner = crate::ID)]
+    pub attestation: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: Legacy-layout config; discriminator verified manually
+    #[account(mut, owner = crate::ID, seeds = [b"moltlaunch"], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAuthority<'info> {
+    /// CHECK: Legacy-layout authority; discriminator and PDA verified manually
+    #[account(mut, owner = crate::ID)]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct MigrateAgent<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Legacy-layout agent; discriminator and PDA verified manually
+    #[account(mut, owner = crate::ID)]
+    pub agent: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + NameRecord::INIT_SPACE,
+        seeds = [b"name".as_ref(), name_seed(&name).as_ref()],
+        bump
+    )]
+    pub name_record: Account<'info, NameRecord>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RenewAttestation<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"authority", authority_signer.key().as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    #[account(
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            authority_signer.key().as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// Required for InfraTEE: the measurement the quote was produced under
+    #[account(
+        seeds = [
+            b"enclave",
+            enclave_seed(&enclave.vendor, &enclave.kind).as_ref(),
+            enclave.measurement.as_ref(),
+        ],
+        bump = enclave.bump,
+    )]
+    pub enclave: Option<Account<'info, EnclaveMeasurement>>,
+
+    pub authority_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredAttestation<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [
+            b"attestation",
+            attestation.agent.as_ref(),
+            attestation.authority.as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// Required unless the attestation is revoked
+    #[account(
+        mut,
+        seeds = [b"agent", attestation.agent.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Option<Account<'info, AgentIdentity>>,
+
+    /// CHECK: Receives the remaining rent
+    #[account(mut, address = attestation.rent_recipient())]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRevokedBatchClaim<'info> {
+    #[account(address = attestation.batch)]
+    pub batch: Account<'info, AttestationBatch>,
+
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [
+            b"attestation",
+            attestation.agent.as_ref(),
+            attestation.authority.as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", attestation.agent.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    /// CHECK: Receives the rent
+    #[account(mut, address = attestation.rent_recipient())]
+    pub rent_recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    signal_type: SignalType,
+    attestation_hash: [u8; 32],
+    tee_quote: Option<TeeQuote>,
+    expires_at: i64,
+    index: u8,
+)]
+pub struct SubmitSignedAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", authority.pubkey.as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Attestation::INIT_SPACE,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            authority.pubkey.as_ref(),
+            attestation_index_seed(&index),
+        ],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// Required for InfraTEE: the measurement the quote was produced under
+    #[account(
+        seeds = [
+            b"enclave",
+            enclave_seed(&enclave.vendor, &enclave.kind).as_ref(),
+            enclave.measurement.as_ref(),
+        ],
+        bump = enclave.bump,
+    )]
+    pub enclave: Option<Account<'info, EnclaveMeasurement>>,
+
+    /// This authority's earlier attestation of the agent being superseded
+    #[account(
+        mut,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            authority.pubkey.as_ref(),
+            previous.index_seed(),
+        ],
+        bump = previous.bump,
+    )]
+    pub previous: Option<Account<'info, Attestation>>,
+
+    /// CHECK: Instructions sysvar, read to find the Ed25519 signature check
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub relayer: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u32)]
+pub struct RegisterSchema<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Schema::INIT_SPACE,
+        seeds = [b"schema", id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub schema: Account<'info, Schema>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(signal_type: SignalType)]
+pub struct RegisterBuiltinSchema<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Schema::INIT_SPACE,
+        seeds = [b"schema", signal_type.schema_id().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub schema: Account<'info, Schema>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSchemaActive<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"schema", schema.id.to_le_bytes().as_ref()],
+        bump = schema.bump,
+    )]
+    pub schema: Account<'info, Schema>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(schema_id: u32, attestation_hash: [u8; 32], expires_at: i64, index: u8)]
+pub struct SubmitSchemaAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", authority_signer.key().as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    #[account(
+        seeds = [b"schema", schema_id.to_le_bytes().as_ref()],
+        bump = schema.bump,
+    )]
+    pub schema: Account<'info, Schema>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Attestation::INIT_SPACE,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            authority_signer.key().as_ref(),
+            attestation_index_seed(&index),
+        ],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// This authority's earlier attestation of the agent being superseded
+    #[account(
+        mut,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            authority_signer.key().as_ref(),
+            previous.index_seed(),
+        ],
+        bump = previous.bump,
+    )]
+    pub previous: Option<Account<'info, Attestation>>,
+
+    pub authority_signer: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(data: EvidenceData)]
+pub struct AttachEvidence<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [
+            b"attestation",
+            attestation.agent.as_ref(),
+            authority_signer.key().as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = Evidence::space(&data),
+        seeds = [b"evidence", attestation.key().as_ref()],
+        bump
+    )]
+    pub evidence: Account<'info, Evidence>,
+
+    pub authority_signer: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEvidence<'info> {
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [b"evidence", evidence.attestation.as_ref()],
+        bump = evidence.bump,
+        constraint = evidence.authority == authority_signer.key() @ MoltError::Unauthorized,
+    )]
+    pub evidence: Account<'info, Evidence>,
+
+    /// CHECK: Receives the rent; whoever funded the evidence record
+    #[account(mut, address = evidence.payer)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub authority_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitAttestationsBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", authority_signer.key().as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    pub authority_signer: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(batch_id: u64)]
+pub struct PostAttestationBatch<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"authority", authority_signer.key().as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AttestationBatch::INIT_SPACE,
+        seeds = [
+            b"attestation_batch",
+            authority_signer.key().as_ref(),
+            batch_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub batch: Account<'info, AttestationBatch>,
+
+    pub authority_signer: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimBatchAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", batch.authority.as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"attestation_batch",
+            batch.authority.as_ref(),
+            batch.batch_id.to_le_bytes().as_ref(),
+        ],
+        bump = batch.bump,
+    )]
+    pub batch: Account<'info, AttestationBatch>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.wallet.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Attestation::INIT_SPACE,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            batch.authority.as_ref(),
+            batch.key().as_ref(),
+        ],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// The batch authority's earlier attestation of the agent being superseded
+    #[account(
+        mut,
+        seeds = [
+            b"attestation",
+            agent.wallet.as_ref(),
+            batch.authority.as_ref(),
+            previous.index_seed(),
+        ],
+        bump = previous.bump,
+    )]
+    pub previous: Option<Account<'info, Attestation>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestationBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"attestation_batch",
+            authority_signer.key().as_ref(),
+            batch.batch_id.to_le_bytes().as_ref(),
+        ],
+        bump = batch.bump,
+    )]
+    pub batch: Account<'info, AttestationBatch>,
+
+    pub authority_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(vendor: TeeVendor, kind: MeasurementKind, measurement: [u8; 32])]
+pub struct RegisterEnclaveMeasurement<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + EnclaveMeasurement::INIT_SPACE,
+        seeds = [
+            b"enclave",
+            enclave_seed(&vendor, &kind).as_ref(),
+            measurement.as_ref(),
+        ],
+        bump
+    )]
+    pub enclave: Account<'info, EnclaveMeasurement>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeEnclaveMeasurement<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"enclave",
+            enclave_seed(&enclave.vendor, &enclave.kind).as_ref(),
+            enclave.measurement.as_ref(),
+        ],
+        bump = enclave.bump,
+    )]
+    pub enclave: Account<'info, EnclaveMeasurement>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ChallengeAttestation<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"authority", authority_signer.key().as_ref()],
+        bump = authority.bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"attestation",
+            attestation.agent.as_ref(),
+            attestation.authority.as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AttestationChallenge::INIT_SPACE,
+        seeds = [
+            b"challenge",
+            attestation.key().as_ref(),
+            authority_signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub challenge: Account<'info, AttestationChallenge>,
+
+    pub authority_signer: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveChallenge<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"challenge",
+            attestation.key().as_ref(),
+            challenge.challenger.as_ref(),
+        ],
+        bump = challenge.bump,
+    )]
+    pub challenge: Account<'info, AttestationChallenge>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"attestation",
+            attestation.agent.as_ref(),
+            attestation.authority.as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", attestation.agent.as_ref()],
+        bump = agent.bump,
+    )]
+    pub agent: Account<'info, AgentIdentity>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseChallenge<'info> {
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [
+            b"challenge",
+            challenge.attestation.as_ref(),
+            challenge.challenger.as_ref(),
+        ],
+        bump = challenge.bump,
+    )]
+    pub challenge: Account<'info, AttestationChallenge>,
+
+    /// CHECK: The challenged attestation; only checked for having been closed
+    /// or recreated
+    #[account(address = challenge.attestation)]
+    pub attestation: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the rent; whoever funded the challenge
+    #[account(mut, address = challenge.payer)]
+    pub rent_recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BindEnclaveMeasurement<'info> {
+    #[account(
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
         seeds = [b"authority", authority_signer.key().as_ref()],
         bump = authority.bump,
     )]
     pub authority: Account<'info, Authority>,
 
-    #[account(
-        mut,
-        seeds = [b"agent", agent.wallet.as_ref()],
-        bump = agent.bump,
-    )]
-    pub agent: Account<'info, AgentIdentity>,
+    #[account(
+        mut,
+        seeds = [
+            b"attestation",
+            attestation.agent.as_ref(),
+            authority_signer.key().as_ref(),
+            attestation.index_seed(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        seeds = [
+            b"enclave",
+            enclave_seed(&enclave.vendor, &enclave.kind).as_ref(),
+            enclave.measurement.as_ref(),
+        ],
+        bump = enclave.bump,
+    )]
+    pub enclave: Account<'info, EnclaveMeasurement>,
+
+    pub authority_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"moltlaunch"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+// =============================================================================
+// State Accounts
+// =============================================================================
+
+/// Singleton protocol configuration — seeds: ["moltlaunch"]
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolConfig {
+    pub admin: Pubkey,           // 32
+    pub revocation_nonce: u64,   // 8
+    pub total_agents: u64,       // 8
+    pub total_attestations: u64, // 8
+    pub paused: bool,            // 1
+    pub bump: u8,                // 1
+    pub operator_inheritance: OperatorInheritance, // 1
+    pub reset_controller_signals_on_transfer: bool, // 1
+    pub liveness_window: i64,    // 8 — seconds; 0 disables the liveness penalty
+    pub expiry_grace_period: i64, // 8 — seconds after expiry before anyone may close
+    pub cranker_tip: u64,        // 8 — lamports paid to the closer, <= MAX_CRANKER_TIP
+    pub challenge_periods: [i64; SIGNAL_TYPE_COUNT], // 8 * 6 — seconds, by SignalType; 0 = none
+}
+
+impl ProtocolConfig {
+    /// When a `signal_type` attestation submitted at `now` takes effect;
+    /// 0 when its signal type has no challenge period.
+    pub fn finalizes_at(&self, signal_type: &SignalType, now: i64) -> i64 {
+        match self.challenge_periods[signal_type.clone() as usize] {
+            0 => 0,
+            period => now.saturating_add(period),
+        }
+    }
+}
+
+/// Authority — one per authorized verifier. Seeds: ["authority", pubkey]
+#[account]
+#[derive(InitSpace)]
+pub struct Authority {
+    pub pubkey: Pubkey,                  // 32
+    pub authority_type: AuthorityType,   // 1
+    pub attestation_count: u64,          // 8
+    pub active: bool,                    // 1
+    pub added_by: Pubkey,                // 32
+    pub added_at: i64,                   // 8
+    pub bump: u8,                        // 1
+    pub last_signed_nonce: u64,          // 8 — highest nonce relayed via submit_signed_attestation
+    pub open_signed_nonces: u128,        // 16 — bit i set: nonce last_signed_nonce - 1 - i still unused
+}
+
+/// AgentIdentity — the composable signal hub. Seeds: ["agent", wallet]
+/// `wallet` is the registering key and never changes; `owner` is the wallet
+/// currently in control and moves with accept_agent_transfer.
+#[account]
+#[derive(InitSpace)]
+pub struct AgentIdentity {
+    pub wallet: Pubkey,              // 32
+    pub infra_type: InfraType,       // 1
+    pub has_economic_stake: bool,    // 1
+    pub has_hardware_binding: bool,  // 1
+    pub attestation_count: u8,       // 1
+    pub is_flagged: bool,            // 1
+    pub trust_score: u8,             // 1
+    pub last_verified: i64,          // 8
+    pub nonce: u64,                  // 8
+    pub registered_at: i64,          // 8
+    #[max_len(32)]
+    pub name: String,                // 4 + 32
+    pub bump: u8,                    // 1
+    pub live_attestations: u32,      // 4 — submitted, not yet revoked
+    pub name_changed_at: i64,        // 8
+    pub operator: Option<Pubkey>,    // 1 + 32 — operator authority, if linked
+    pub owner: Pubkey,               // 32
+    pub pending_owner: Option<Pubkey>, // 1 + 32
+    pub signals_reset_at: i64,       // 8 — last transfer that reset controller-bound signals
+    pub last_active: i64,            // 8 — last heartbeat
+    pub heartbeat_streak: u32,       // 4
+    pub status: AgentStatus,         // 1
+    pub previous_status: AgentStatus, // 1
+    pub status_changed_at: i64,      // 8
+    pub emergency_key: Option<Pubkey>, // 1 + 32 — may freeze, never thaw
+    pub guardian: Option<Pubkey>,    // 1 + 32 — may thaw after GUARDIAN_THAW_DELAY
+    pub is_frozen: bool,             // 1
+    pub frozen_at: i64,              // 8
+    pub requires_consent: bool,      // 1 — new attestations land pending
+    pub fraud_revocations: u32,      // 4 — attestations revoked for Fraud
+    pub pending_guardian: Option<Pubkey>, // 1 + 32 — replaces guardian at guardian_change_at
+    pub guardian_change_at: i64,     // 8 — 0 when no change is pending
+    pub payer: Pubkey,               // 32 — refunded by close_agent
+}
+
+impl AgentIdentity {
+    /// Who gets the rent back on close. Agents that predate the payer field
+    /// were funded by their registering wallet.
+    pub fn rent_recipient(&self) -> Pubkey {
+        if self.payer == Pubkey::default() {
+            self.wallet
+        } else {
+            self.payer
+        }
+    }
+
+    /// Drops the emergency key, guardian and any pending guardian change.
+    /// Returns whether any of them was set.
+    pub fn clear_recovery_keys(&mut self) -> bool {
+        let was_set = self.emergency_key.is_some()
+            || self.guardian.is_some()
+            || self.pending_guardian.is_some();
+        self.emergency_key = None;
+        self.guardian = None;
+        self.pending_guardian = None;
+        self.guardian_change_at = 0;
+        was_set
+    }
+
+    /// Drops the signals bound to the controlling wallet. The trust score is
+    /// zeroed rather than adjusted, since penalties and saturation make the
+    /// dropped weights unrecoverable; refresh_identity_signals rebuilds it
+    /// without attestations verified before `now`.
+    pub fn reset_controller_signals(&mut self, now: i64) {
+        self.has_economic_stake = false;
+        self.has_hardware_binding = false;
+        self.trust_score = 0;
+        self.signals_reset_at = now;
+    }
+
+    /// Applies a pending guardian change whose delay has elapsed by `now`.
+    pub fn settle_guardian(&mut self, now: i64) {
+        if self.guardian_change_at != 0 && now >= self.guardian_change_at {
+            self.guardian = self.pending_guardian.take();
+            self.guardian_change_at = 0;
+        }
+    }
+
+    /// Score deducted at refresh when no heartbeat landed within the liveness window.
+    pub const LIVENESS_PENALTY: u8 = 15;
+
+    /// Score deducted at refresh per attestation revoked for Fraud.
+    pub const FRAUD_REVOCATION_PENALTY: u8 = 25;
+
+    /// Where unban_agent leaves a banned agent: Retired stays permanent,
+    /// anything else waits in Suspended for unflag_agent.
+    pub fn status_after_unban(&self) -> AgentStatus {
+        if self.previous_status == AgentStatus::Retired {
+            AgentStatus::Retired
+        } else {
+            AgentStatus::Suspended
+        }
+    }
+
+    /// Moves the agent to `to`, recording the previous status and emitting
+    /// AgentStatusChanged. No-op if the agent is already in `to`.
+    pub fn transition(&mut self, to: AgentStatus, now: i64) {
+        if self.status == to {
+            return;
+        }
+        let from = std::mem::replace(&mut self.status, to.clone());
+        self.previous_status = from.clone();
+        self.status_changed_at = now;
+
+        emit!(AgentStatusChanged {
+            agent: self.wallet,
+            from,
+            to,
+        });
+    }
+
+    /// Counts a newly effective attestation: applies its signal, bumps the
+    /// count and activates a Registered agent.
+    pub fn record_attestation(&mut self, signal: &SignalType, now: i64) {
+        self.apply_signal(signal);
+        self.attestation_count = self.attestation_count.saturating_add(1);
+        self.last_verified = now;
+        if self.status == AgentStatus::Registered {
+            self.transition(AgentStatus::Active, now);
+        }
+    }
+
+    /// Folds one signal into the agent's flags — upgrade only, never downgrade.
+    pub fn apply_signal(&mut self, signal: &SignalType) {
+        match signal {
+            SignalType::InfraCloud => {
+                if (self.infra_type.clone() as u8) < (InfraType::Cloud as u8) {
+                    self.infra_type = InfraType::Cloud;
+                }
+            }
+            SignalType::InfraTEE => {
+                if (self.infra_type.clone() as u8) < (InfraType::TEE as u8) {
+                    self.infra_type = InfraType::TEE;
+                }
+            }
+            SignalType::InfraDePIN => {
+                self.infra_type = InfraType::DePIN; // highest
+            }
+            SignalType::EconomicStake => self.has_economic_stake = true,
+            SignalType::HardwareBinding => self.has_hardware_binding = true,
+            SignalType::General => {}
+        }
+    }
+}
+
+/// NameRecord — reserves a name for one agent.
+/// Seeds: ["name", name_seed(name)] — the confusable-folded skeleton, so
+/// look-alike names such as "paypa1" and "paypal" collide.
+#[account]
+#[derive(InitSpace)]
+pub struct NameRecord {
+    pub agent: Pubkey,               // 32 — agent wallet
+    #[max_len(32)]
+    pub name: String,                // 4 + 32 — normalized display form
+    pub claimed_at: i64,             // 8
+    pub bump: u8,                    // 1
+    pub payer: Pubkey,               // 32 — refunded when the name is released
+}
+
+/// AgentTombstone — left behind by close_agent. Seeds: ["tombstone", wallet]
+#[account]
+#[derive(InitSpace)]
+pub struct AgentTombstone {
+    pub wallet: Pubkey,              // 32
+    pub was_flagged: bool,           // 1 — sticky across closes
+    pub closed_at: i64,              // 8
+    pub close_count: u32,            // 4
+    pub bump: u8,                    // 1
+    pub fraud_revocations: u32,      // 4 — restored on re-registration
+}
+
+impl AgentTombstone {
+    /// Carries what the closed identity cannot shed over to `agent`, a
+    /// fresh registration of the same wallet.
+    pub fn restore(&self, agent: &mut AgentIdentity, now: i64) {
+        agent.fraud_revocations = self.fraud_revocations;
+        if self.was_flagged {
+            agent.is_flagged = true;
+            agent.transition(AgentStatus::Suspended, now);
+        }
+    }
+}
+
+/// Attestation — one per (agent, authority, index).
+/// Seeds: ["attestation", agent_wallet, authority_pubkey, index_seed]
+/// Fields are only ever appended, and every new field must read as zero for
+/// attestations created before it existed (see migrate_attestation).
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation {
+    pub agent: Pubkey,                       // 32
+    pub authority: Pubkey,                   // 32
+    pub authority_type: AuthorityType,       // 1
+    pub signal_contributed: SignalType,       // 1
+    pub attestation_hash: [u8; 32],          // 32
+    pub tee_quote: Option<[u8; 32]>,         // 1 + 32
+    pub created_at: i64,                     // 8
+    pub expires_at: i64,                     // 8
+    pub revoked: bool,                       // 1
+    pub bump: u8,                            // 1
+    pub pending: bool,                       // 1 — awaiting agent consent
+    pub index: u8,                           // 1 — per (agent, authority) pair
+    pub renewal_count: u32,                  // 4
+    pub previous_hash: [u8; 32],             // 32 — hash replaced by the last renewal
+    pub renewed_at: i64,                     // 8 — 0 if never renewed
+    pub payer: Pubkey,                       // 32 — funded the rent; default if legacy
+    pub revocation_reason: RevocationReason, // 1
+    pub revocation_reason_hash: [u8; 32],    // 32 — off-chain evidence for the reason
+    pub revoked_at: i64,                     // 8 — 0 if not revoked
+    pub relayer: Pubkey,                     // 32 — default unless submitted via relay
+    pub schema_id: u32,                      // 4 — 0 on legacy attestations; see SignalType::schema_id
+    pub schema_weight: u8,                   // 1 — custom schema weight at submission
+    pub batch: Pubkey,                       // 32 — AttestationBatch claimed from; default if none
+    pub enclave_measurement: Pubkey,         // 32 — EnclaveMeasurement for InfraTEE; default otherwise
+    pub finalizes_at: i64,                   // 8 — end of the challenge window; 0 if none
+    pub open_challenges: u16,                // 2 — held while any challenge is unresolved
+    pub supersedes: Pubkey,                  // 32 — attestation this one replaced; default if none
+    pub superseded_by: Pubkey,               // 32 — replacement, once superseded
+    pub sequence: u32,                       // 4 — position in its supersession chain
+}
+
+impl Attestation {
+    /// Whether the signal counts: not revoked, accepted if consent was
+    /// required, and past an unchallenged challenge window.
+    pub fn is_effective(&self, now: i64) -> bool {
+        !self.revoked && !self.pending && self.open_challenges == 0 && self.finalizes_at <= now
+    }
+
+    /// Who gets the rent back on close. Attestations that predate the payer
+    /// field were funded by their authority.
+    pub fn rent_recipient(&self) -> Pubkey {
+        if self.payer == Pubkey::default() {
+            self.authority
+        } else {
+            self.payer
+        }
+    }
+
+    /// When the authority last vouched for this signal: creation or renewal.
+    pub fn verified_at(&self) -> i64 {
+        self.created_at.max(self.renewed_at)
+    }
+
+    /// Seed suffix for this attestation's address. Batch claims are keyed by
+    /// their batch, so they cannot collide with direct attestations or with
+    /// claims from other batches; everything else by attestation_index_seed.
+    pub fn index_seed(&self) -> &[u8] {
+        if self.batch == Pubkey::default() {
+            attestation_index_seed(&self.index)
+        } else {
+            self.batch.as_ref()
+        }
+    }
+
+    /// Whether `next` may replace this attestation: same signal and schema,
+    /// and not already superseded. Schema id 0 predates schemas and so is
+    /// always its signal's built-in schema.
+    pub fn can_be_superseded_by(&self, next: &Attestation) -> bool {
+        let schema_id = match self.schema_id {
+            0 => self.signal_contributed.schema_id(),
+            id => id,
+        };
+        self.superseded_by == Pubkey::default()
+            && self.signal_contributed == next.signal_contributed
+            && schema_id == next.schema_id
+    }
+
+    /// Deserializes an attestation that may predate fields appended since it
+    /// was written. Missing trailing bytes are read as zero.
+    pub fn try_deserialize_padded(data: &[u8]) -> Result<Self> {
+        try_deserialize_padded(data)
+    }
+}
+
+/// Deserializes an account that may predate fields appended to its layout
+/// since it was written. Missing trailing bytes are read as zero.
+pub fn try_deserialize_padded<T: AccountDeserialize + Space>(data: &[u8]) -> Result<T> {
+    let mut buf = data.to_vec();
+    if buf.len() < 8 + T::INIT_SPACE {
+        buf.resize(8 + T::INIT_SPACE, 0);
+    }
+    T::try_deserialize(&mut &buf[..])
+}
+
+/// Grows a legacy-layout account to `new_len`, topping its rent up from
+/// `payer` first.
+pub fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(info.lamports());
+    if shortfall > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(new_len)?;
+    Ok(())
+}
+
+/// Links `attestation` into the supersession chain after `previous` and
+/// retires `previous`. Not counted as a revocation: the signal is still
+/// vouched for, so the nonce stays put.
+pub fn supersede_attestation(
+    attestation: &mut Account<Attestation>,
+    previous: &mut Account<Attestation>,
+    agent: &mut AgentIdentity,
+    now: i64,
+) -> Result<()> {
+    require!(
+        previous.can_be_superseded_by(attestation),
+        MoltError::InvalidSupersession
+    );
+    attestation.supersedes = previous.key();
+    attestation.sequence = previous.sequence.checked_add(1).unwrap();
+    previous.superseded_by = attestation.key();
+    if !previous.revoked {
+        previous.revoked = true;
+        previous.revoked_at = now;
+        previous.revocation_reason = RevocationReason::Superseded;
+        agent.live_attestations = agent.live_attestations.saturating_sub(1);
+    }
+
+    emit!(AttestationSuperseded {
+        agent: agent.wallet,
+        authority: attestation.authority,
+        signal_type: attestation.signal_contributed.clone(),
+        previous: previous.key(),
+        attestation: attestation.key(),
+        sequence: attestation.sequence,
+    });
+
+    Ok(())
+}
+
+/// Seed suffix for an attestation index. Index 0 contributes no bytes, so the
+/// first attestation of each (agent, authority) pair keeps the address used
+/// before indexes existed: ["attestation", agent_wallet, authority_pubkey].
+pub fn attestation_index_seed(index: &u8) -> &[u8] {
+    if *index == 0 {
+        &[]
+    } else {
+        std::slice::from_ref(index)
+    }
+}
+
+/// InfraTEE attestations must carry a quote whose enclave identity matches
+/// an active, registered enclave measurement. Returns that measurement's
+/// address (the default key for other signals) and the digest to store.
+pub fn check_tee_quote(
+    signal_type: &SignalType,
+    tee_quote: &Option<TeeQuote>,
+    enclave: Option<&Account<EnclaveMeasurement>>,
+) -> Result<(Pubkey, Option<[u8; 32]>)> {
+    let digest = tee_quote.as_ref().map(TeeQuote::digest);
+    if *signal_type != SignalType::InfraTEE {
+        return Ok((Pubkey::default(), digest));
+    }
+    let quote = tee_quote.as_ref().ok_or(MoltError::TeeQuoteRequired)?;
+    let enclave = enclave.ok_or(MoltError::TeeQuoteRequired)?;
+    require!(enclave.active, MoltError::EnclaveMeasurementInactive);
+    require!(
+        quote.vendor == enclave.vendor
+            && quote.kind == enclave.kind
+            && quote.measurement == enclave.measurement,
+        MoltError::TeeQuoteMismatch
+    );
+    Ok((enclave.key(), digest))
+}
+
+/// Seed component separating identical measurement bytes registered under
+/// different TEE vendors or measurement kinds.
+pub fn enclave_seed(vendor: &TeeVendor, kind: &MeasurementKind) -> [u8; 2] {
+    [vendor.clone() as u8, kind.clone() as u8]
+}
+
+/// Longest challenge period the admin may configure (30 days).
+pub const MAX_CHALLENGE_PERIOD: i64 = 30 * 24 * 60 * 60;
+
+/// Most (agent, attestation) pairs one submit_attestations_batch accepts.
+pub const MAX_BATCH_ATTESTATIONS: usize = 16;
+
+/// Upper bound on ProtocolConfig.cranker_tip (0.0001 SOL), well below the rent
+/// of an attestation so the original payer always gets most of it back.
+pub const MAX_CRANKER_TIP: u64 = 100_000;
+
+/// AgentProfile — descriptive metadata for an agent. Seeds: ["profile", agent_wallet]
+/// Variable-size: reallocated to fit on every update.
+#[account]
+pub struct AgentProfile {
+    pub agent: Pubkey,                   // 32 — agent wallet
+    pub metadata_uri: String,            // 4 + len
+    pub content_hash: [u8; 32],          // 32 — hash of the document at metadata_uri
+    pub model: String,                   // 4 + len — model or framework identifier
+    pub capabilities: Vec<String>,       // 4 + Σ(4 + len)
+    pub endpoints: Vec<ServiceEndpoint>, // 4 + Σ(8 + len)
+    pub contact: String,                 // 4 + len
+    pub version: u32,                    // 4 — bumped on every update
+    pub updated_at: i64,                 // 8
+    pub bump: u8,                        // 1
+}
+
+impl AgentProfile {
+    /// Total account size (including discriminator) needed to hold `data`.
+    pub fn space(data: &AgentProfileData) -> usize {
+        8 + 32
+            + 4 + data.metadata_uri.len()
+            + 32
+            + 4 + data.model.len()
+            + 4 + data.capabilities.iter().map(|c| 4 + c.len()).sum::<usize>()
+            + 4 + data.endpoints.iter().map(|e| 8 + e.kind.len() + e.uri.len()).sum::<usize>()
+            + 4 + data.contact.len()
+            + 4
+            + 8
+            + 1
+    }
+
+    fn apply(&mut self, data: AgentProfileData, now: i64) {
+        self.metadata_uri = data.metadata_uri;
+        self.content_hash = data.content_hash;
+        self.model = data.model;
+        self.capabilities = data.capabilities;
+        self.endpoints = data.endpoints;
+        self.contact = data.contact;
+        self.updated_at = now;
+    }
+}
+
+/// Evidence — what an attestation's hash covers and where to fetch it.
+/// Seeds: ["evidence", attestation]
+/// Variable-size: sized to its contents at creation.
+#[account]
+pub struct Evidence {
+    pub attestation: Pubkey,             // 32
+    pub authority: Pubkey,               // 32
+    pub content_hash: [u8; 32],          // 32 — attestation_hash when attached
+    pub hash_algorithm: HashAlgorithm,   // 1 — how content_hash was computed
+    pub uri: String,                     // 4 + len — empty if the payload is inline
+    pub media_type: String,              // 4 + len — e.g. "application/json"
+    pub payload: Vec<u8>,                // 4 + len — inline evidence, may be empty
+    pub created_at: i64,                 // 8
+    pub bump: u8,                        // 1
+    pub payer: Pubkey,                   // 32 — refunded by close_evidence
+}
+
+impl Evidence {
+    /// Total account size (including discriminator) needed to hold `data`.
+    pub fn space(data: &EvidenceData) -> usize {
+        8 + 32
+            + 32
+            + 32
+            + 1
+            + 4 + data.uri.len()
+            + 4 + data.media_type.len()
+            + 4 + data.payload.len()
+            + 8
+            + 1
+            + 32
+    }
+
+    fn apply(&mut self, data: EvidenceData) {
+        self.hash_algorithm = data.hash_algorithm;
+        self.uri = data.uri;
+        self.media_type = data.media_type;
+        self.payload = data.payload;
+    }
+}
+
+/// SessionKey — an ephemeral key allowed to act for an agent.
+/// Seeds: ["session", agent_wallet, session_key]
+#[account]
+#[derive(InitSpace)]
+pub struct SessionKey {
+    pub agent: Pubkey,               // 32 — agent wallet
+    pub owner: Pubkey,               // 32 — owner that created it; void after a transfer
+    pub session_key: Pubkey,         // 32
+    pub allowed_actions: u8,         // 1 — bitmask of SessionKey::* actions
+    pub created_at: i64,             // 8
+    pub expires_at: i64,             // 8
+    pub bump: u8,                    // 1
+    pub payer: Pubkey,               // 32 — refunded by revoke_session_key
+}
+
+impl SessionKey {
+    pub const HEARTBEAT: u8 = 1 << 0;
+    pub const PROFILE_UPDATE: u8 = 1 << 1;
+    pub const GATE_CHECK: u8 = 1 << 2;
+    pub const ALL_ACTIONS: u8 = Self::HEARTBEAT | Self::PROFILE_UPDATE | Self::GATE_CHECK;
+
+    /// Longest lifetime a session key may be created with (30 days).
+    pub const MAX_DURATION: i64 = 30 * 24 * 60 * 60;
+
+    /// True if this session lets `signer` perform every action in `action` for `agent`.
+    pub fn authorizes(&self, agent: &AgentIdentity, signer: &Pubkey, action: u8, now: i64) -> bool {
+        self.agent == agent.wallet
+            && self.owner == agent.owner
+            && self.session_key == *signer
+            && self.allowed_actions & action == action
+            && now < self.expires_at
+    }
+}
+
+/// Operator — the team or entity controlling a fleet of agents.
+/// Seeds: ["operator", operator_authority]
+#[account]
+#[derive(InitSpace)]
+pub struct Operator {
+    pub authority: Pubkey,           // 32
+    #[max_len(32)]
+    pub name: String,                // 4 + 32
+    pub agent_count: u32,            // 4
+    pub is_flagged: bool,            // 1 — zeroes every linked agent's score
+    pub created_at: i64,             // 8
+    pub bump: u8,                    // 1
+}
+
+/// Schema — a registered kind of signal. Ids 1..=SIGNAL_TYPE_COUNT describe
+/// the built-in SignalTypes; custom ones start at FIRST_CUSTOM_SCHEMA_ID.
+/// Seeds: ["schema", id (u32 LE)]
+#[account]
+#[derive(InitSpace)]
+pub struct Schema {
+    pub id: u32,                     // 4 — SignalType::schema_id() or >= FIRST_CUSTOM_SCHEMA_ID
+    #[max_len(32)]
+    pub name: String,                // 4 + 32
+    pub layout_hash: [u8; 32],       // 32 — hash of the off-chain field layout
+    pub default_lifetime: i64,       // 8 — seconds, used when expires_at = 0
+    pub weight: u8,                  // 1 — score contribution while attested
+    #[max_len(8)]
+    pub allowed_authorities: Vec<Pubkey>, // 4 + 8 * 32 — empty = any active authority
+    pub active: bool,                // 1
+    pub created_at: i64,             // 8
+    pub bump: u8,                    // 1
+}
+
+impl Schema {
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_ALLOWED_AUTHORITIES: usize = 8;
+
+    pub fn allows(&self, authority: &Pubkey) -> bool {
+        self.allowed_authorities.is_empty() || self.allowed_authorities.contains(authority)
+    }
+}
+
+/// Schema ids below this are reserved for the built-in SignalTypes, whose
+/// Schema accounts register_builtin_schema creates. Their scoring stays in
+/// refresh_identity_signals, at SignalType::weight, and they are always
+/// active and open to every authority.
+pub const FIRST_CUSTOM_SCHEMA_ID: u32 = 64;
+
+/// OperatorAttestation — one per (operator, authority) pair.
+/// Seeds: ["operator_attestation", operator_authority, authority_pubkey]
+#[account]
+#[derive(InitSpace)]
+pub struct OperatorAttestation {
+    pub operator: Pubkey,                    // 32 — operator authority
+    pub authority: Pubkey,                   // 32
+    pub signal_contributed: SignalType,      // 1
+    pub attestation_hash: [u8; 32],          // 32
+    pub created_at: i64,                     // 8
+    pub expires_at: i64,                     // 8
+    pub bump: u8,                            // 1
+    pub payer: Pubkey,                       // 32 — refunded by revoke_operator_attestation
+}
+
+/// AttestationBatch — a Merkle root over many agents' attestations, claimed
+/// one agent at a time. Seeds: ["attestation_batch", authority, batch_id (u64 LE)]
+#[account]
+#[derive(InitSpace)]
+pub struct AttestationBatch {
+    pub authority: Pubkey,                   // 32
+    pub batch_id: u64,                       // 8
+    pub merkle_root: [u8; 32],               // 32 — over batch_leaf leaves
+    pub leaf_count: u32,                     // 4 — caps claimed_count
+    pub claimed_count: u32,                  // 4
+    pub index: u8,                           // 1 — attestation index claims record
+    pub expires_at: i64,                     // 8 — caps every claim's expiry
+    pub revoked: bool,                       // 1
+    pub revoked_at: i64,                     // 8
+    pub created_at: i64,                     // 8
+    pub bump: u8,                            // 1
+}
+
+/// EnclaveMeasurement — an approved TEE build that InfraTEE attestations may
+/// cite. Seeds: ["enclave", enclave_seed(vendor, kind), measurement]
+#[account]
+#[derive(InitSpace)]
+pub struct EnclaveMeasurement {
+    pub vendor: TeeVendor,                   // 1
+    pub kind: MeasurementKind,               // 1
+    pub measurement: [u8; 32],               // 32 — MRENCLAVE, MRSIGNER or MRTD
+    #[max_len(32)]
+    pub label: String,                       // 4 + 32
+    pub active: bool,                        // 1
+    pub added_by: Pubkey,                    // 32
+    pub added_at: i64,                       // 8
+    pub revoked_at: i64,                     // 8 — 0 while active
+    pub bump: u8,                            // 1
+}
+
+impl EnclaveMeasurement {
+    pub const MAX_LABEL_LEN: usize = 32;
+}
+
+/// AttestationChallenge — one authority's dispute of another's attestation.
+/// Seeds: ["challenge", attestation, challenger]
+#[account]
+#[derive(InitSpace)]
+pub struct AttestationChallenge {
+    pub attestation: Pubkey,                 // 32
+    pub challenger: Pubkey,                  // 32 — authority pubkey
+    pub evidence_hash: [u8; 32],             // 32
+    pub created_at: i64,                     // 8
+    pub resolved: bool,                      // 1
+    pub upheld: bool,                        // 1 — meaningful once resolved
+    pub resolved_at: i64,                    // 8
+    pub bump: u8,                            // 1
+    pub payer: Pubkey,                       // 32 — refunded by close_challenge
+    pub attestation_created_at: i64,         // 8 — tells a recreated attestation apart
+}
+
+impl AttestationChallenge {
+    /// Whether the account at this challenge's attestation address is no
+    /// longer the attestation challenged: closed, or recreated since.
+    pub fn is_stale(&self, attestation_data: &[u8]) -> bool {
+        if attestation_data.len() < 8 || attestation_data[..8] != *Attestation::DISCRIMINATOR {
+            return true;
+        }
+        match Attestation::try_deserialize_padded(attestation_data) {
+            Ok(attestation) => attestation.created_at != self.attestation_created_at,
+            Err(_) => true,
+        }
+    }
+}
+
+/// LinkedAddress — an EVM address proven to belong to an agent.
+/// Seeds: ["evm", agent_wallet, evm_address]
+/// Proven by the owner at link time; stale once the agent changes owner.
+/// The new owner can unlink it and, with a fresh proof, link it again.
+#[account]
+#[derive(InitSpace)]
+pub struct LinkedAddress {
+    pub agent: Pubkey,               // 32 — agent wallet
+    pub evm_address: [u8; 20],       // 20
+    pub linked_at: i64,              // 8
+    pub bump: u8,                    // 1
+    pub owner: Pubkey,               // 32 — agent owner that linked it
+    pub payer: Pubkey,               // 32 — refunded by unlink_evm_address
+}
+
+impl LinkedAddress {
+    /// Whether the link still speaks for `agent`, i.e. no transfer since.
+    pub fn is_current(&self, agent: &AgentIdentity) -> bool {
+        self.agent == agent.wallet && self.owner == agent.owner
+    }
+}
+
+/// WalletLink — resolves a secondary Solana key to the agent it signs for.
+/// Seeds: ["wallet_link", secondary_wallet] — one identity per key.
+/// Belongs to the owner that linked it; stale once the agent changes owner,
+/// and then removable by the new owner or the secondary key.
+#[account]
+#[derive(InitSpace)]
+pub struct WalletLink {
+    pub agent: Pubkey,               // 32 — agent wallet
+    pub wallet: Pubkey,              // 32 — secondary key
+    pub linked_at: i64,              // 8
+    pub bump: u8,                    // 1
+    pub owner: Pubkey,               // 32 — agent owner that linked it
+    pub payer: Pubkey,               // 32 — refunded by unlink_wallet
+}
+
+impl WalletLink {
+    /// Whether the link still speaks for `agent`, i.e. no transfer since.
+    pub fn is_current(&self, agent: &AgentIdentity) -> bool {
+        self.agent == agent.wallet && self.owner == agent.owner
+    }
+}
+
+// =============================================================================
+// Instruction Arguments
+// =============================================================================
+
+/// Profile contents supplied by the agent wallet.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AgentProfileData {
+    pub metadata_uri: String,
+    pub content_hash: [u8; 32],
+    pub model: String,
+    pub capabilities: Vec<String>,
+    pub endpoints: Vec<ServiceEndpoint>,
+    pub contact: String,
+}
+
+impl AgentProfileData {
+    pub const MAX_URI_LEN: usize = 200;
+    pub const MAX_MODEL_LEN: usize = 64;
+    pub const MAX_CAPABILITIES: usize = 16;
+    pub const MAX_CAPABILITY_LEN: usize = 32;
+    pub const MAX_ENDPOINTS: usize = 8;
+    pub const MAX_ENDPOINT_KIND_LEN: usize = 32;
+    pub const MAX_CONTACT_LEN: usize = 128;
+
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.metadata_uri.len() <= Self::MAX_URI_LEN
+                && self.model.len() <= Self::MAX_MODEL_LEN
+                && self.contact.len() <= Self::MAX_CONTACT_LEN,
+            MoltError::ProfileFieldTooLong
+        );
+        require!(
+            self.capabilities.len() <= Self::MAX_CAPABILITIES
+                && self.endpoints.len() <= Self::MAX_ENDPOINTS,
+            MoltError::ProfileFieldTooLong
+        );
+        require!(
+            self.capabilities
+                .iter()
+                .all(|c| c.len() <= Self::MAX_CAPABILITY_LEN),
+            MoltError::ProfileFieldTooLong
+        );
+        require!(
+            self.endpoints.iter().all(|e| {
+                e.kind.len() <= Self::MAX_ENDPOINT_KIND_LEN && e.uri.len() <= Self::MAX_URI_LEN
+            }),
+            MoltError::ProfileFieldTooLong
+        );
+        Ok(())
+    }
+}
+
+/// Evidence contents supplied by the attesting authority.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EvidenceData {
+    pub uri: String,
+    pub hash_algorithm: HashAlgorithm,
+    pub media_type: String,
+    pub payload: Vec<u8>,
+}
+
+impl EvidenceData {
+    pub const MAX_URI_LEN: usize = 200;
+    pub const MAX_MEDIA_TYPE_LEN: usize = 64;
+    pub const MAX_PAYLOAD_LEN: usize = 512;
+
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.uri.len() <= Self::MAX_URI_LEN
+                && self.media_type.len() <= Self::MAX_MEDIA_TYPE_LEN
+                && self.payload.len() <= Self::MAX_PAYLOAD_LEN,
+            MoltError::EvidenceFieldTooLong
+        );
+        require!(
+            !self.uri.is_empty() || !self.payload.is_empty(),
+            MoltError::EvidenceEmpty
+        );
+        Ok(())
+    }
+}
+
+/// A service the agent exposes, e.g. kind "mcp" or "a2a" plus its URI.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ServiceEndpoint {
+    pub kind: String,
+    pub uri: String,
+}
+
+// =============================================================================
+// Enums
+// =============================================================================
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum AuthorityType {
+    Single,
+    MultisigMember,
+    OracleOperator,
+    NCNValidator,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum InfraType {
+    Unknown,
+    Cloud,
+    TEE,
+    DePIN,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum AgentStatus {
+    Registered,
+    Active,
+    Suspended,
+    Retired,
+    Banned,
+}
+
+impl AgentStatus {
+    /// Registered and Active agents are in service: they can receive
+    /// attestations and carry a non-zero trust score.
+    pub fn is_in_service(&self) -> bool {
+        matches!(self, AgentStatus::Registered | AgentStatus::Active)
+    }
+}
+
+/// What linked agents inherit from their operator's attestations at refresh.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum OperatorInheritance {
+    /// Operator attestations are ignored
+    None,
+    /// Each counts toward the agent's attestation count only
+    CountOnly,
+    /// Each also applies its signal to the agent
+    Full,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum SignalType {
+    InfraCloud,
+    InfraTEE,
+    InfraDePIN,
+    EconomicStake,
+    HardwareBinding,
+    General,
+}
+
+/// Number of SignalType variants; sizes per-signal config tables.
+pub const SIGNAL_TYPE_COUNT: usize = 6;
+
+impl SignalType {
+    /// Signals that describe the controller rather than the agent, and so do
+    /// not carry over to a new owner when the transfer policy resets them.
+    pub fn is_controller_bound(&self) -> bool {
+        matches!(self, SignalType::EconomicStake | SignalType::HardwareBinding)
+    }
+
+    /// The reserved schema id of this built-in signal, 1-based so that 0 on
+    /// an attestation still means "predates schemas".
+    pub fn schema_id(&self) -> u32 {
+        self.clone() as u32 + 1
+    }
+
+    /// Name of this signal's built-in Schema account.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SignalType::InfraCloud => "infra-cloud",
+            SignalType::InfraTEE => "infra-tee",
+            SignalType::InfraDePIN => "infra-depin",
+            SignalType::EconomicStake => "economic-stake",
+            SignalType::HardwareBinding => "hardware-binding",
+            SignalType::General => "general",
+        }
+    }
+
+    /// Score this signal adds at refresh. Infra signals do not stack: only
+    /// the agent's highest infra tier counts. General adds nothing beyond
+    /// the base score every attested agent gets.
+    pub fn weight(&self) -> u8 {
+        match self {
+            SignalType::InfraCloud => 10,
+            SignalType::InfraTEE => 25,
+            SignalType::InfraDePIN => 35,
+            SignalType::EconomicStake => 25,
+            SignalType::HardwareBinding => 20,
+            SignalType::General => 0,
+        }
+    }
+}
+
+/// A batch entry that submit_attestations_batch did not attest.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchSkip {
+    pub position: u8,
+    pub agent: Pubkey,
+    pub reason: BatchSkipReason,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum BatchSkipReason {
+    NotWritable,
+    InvalidAgent,
+    AgentNotEligible,
+    AddressMismatch,
+    AlreadyExists,
+}
+
+/// TEE platform an EnclaveMeasurement belongs to.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum TeeVendor {
+    IntelSgx,
+    IntelTdx,
+    AmdSevSnp,
+    AwsNitro,
+}
+
+/// Which enclave identity an EnclaveMeasurement pins.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum MeasurementKind {
+    MrEnclave,
+    MrSigner,
+    Mrtd,
+}
+
+/// A TEE quote as submitted with an attestation: the enclave identity the
+/// authority read from the quote it verified off-chain, plus a digest of the
+/// raw quote. Only `digest()` is stored, so the identity stays bound to it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct TeeQuote {
+    pub vendor: TeeVendor,
+    pub kind: MeasurementKind,
+    pub measurement: [u8; 32],
+    pub quote_digest: [u8; 32],
+}
+
+impl TeeQuote {
+    pub fn digest(&self) -> [u8; 32] {
+        solana_keccak_hasher::hashv(&[
+            b"MoltLaunch TEE quote v1",
+            &enclave_seed(&self.vendor, &self.kind),
+            &self.measurement,
+            &self.quote_digest,
+        ])
+        .to_bytes()
+    }
+}
 
-    pub authority_signer: Signer<'info>,
+/// Hash function used to derive an attestation_hash from its evidence.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum HashAlgorithm {
+    Sha256,
+    Keccak256,
+    Blake3,
+    Sha3_256,
 }
 
-#[derive(Accounts)]
-pub struct UnflagAgent<'info> {
-    #[account(
-        seeds = [b"moltlaunch"],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, ProtocolConfig>,
+/// Why an authority revoked an attestation. Unspecified is what attestations
+/// revoked before reasons existed read as.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum RevocationReason {
+    Unspecified,
+    KeyRotation,
+    Superseded,
+    IssuedInError,
+    Compromised,
+    Fraud,
+}
 
-    #[account(
-        mut,
-        seeds = [b"agent", agent.wallet.as_ref()],
-        bump = agent.bump,
-    )]
-    pub agent: Account<'info, AgentIdentity>,
+// =============================================================================
+// Events
+// =============================================================================
 
-    pub admin: Signer<'info>,
+#[event]
+pub struct AgentRegistered {
+    pub wallet: Pubkey,
+    pub name: String,
 }
 
-#[derive(Accounts)]
-pub struct RefreshIdentitySignals<'info> {
-    #[account(
-        seeds = [b"moltlaunch"],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, ProtocolConfig>,
+#[event]
+pub struct AgentStatusChanged {
+    pub agent: Pubkey,
+    pub from: AgentStatus,
+    pub to: AgentStatus,
+}
 
-    #[account(
-        mut,
-        seeds = [b"agent", agent.wallet.as_ref()],
-        bump = agent.bump,
-    )]
-    pub agent: Account<'info, AgentIdentity>,
+#[event]
+pub struct RecoveryKeysUpdated {
+    pub agent: Pubkey,
+    pub emergency_key: Option<Pubkey>,
+    pub guardian: Option<Pubkey>,
+    pub guardian_effective_at: i64,
 }
 
-#[derive(Accounts)]
-pub struct CloseAttestation<'info> {
-    #[account(
-        mut,
-        close = authority_signer,
-        seeds = [b"attestation", attestation.agent.as_ref(), attestation.authority.as_ref()],
-        bump = attestation.bump,
-    )]
-    pub attestation: Account<'info, Attestation>,
+#[event]
+pub struct AgentFrozen {
+    pub agent: Pubkey,
+    pub frozen_by: Pubkey,
+}
 
-    #[account(mut)]
-    pub authority_signer: Signer<'info>,
+#[event]
+pub struct AgentThawed {
+    pub agent: Pubkey,
+    pub thawed_by: Pubkey,
+    pub previous_owner: Pubkey,
+    pub owner: Pubkey,
 }
 
-#[derive(Accounts)]
-pub struct AdminAction<'info> {
-    #[account(
-        mut,
-        seeds = [b"moltlaunch"],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, ProtocolConfig>,
+#[event]
+pub struct AgentClosed {
+    pub wallet: Pubkey,
+    pub was_flagged: bool,
+}
 
-    pub admin: Signer<'info>,
+#[event]
+pub struct AgentRenamed {
+    pub wallet: Pubkey,
+    pub old_name: String,
+    pub new_name: String,
 }
 
-// =============================================================================
-// State Accounts
-// =============================================================================
+#[event]
+pub struct AgentProfileUpdated {
+    pub agent: Pubkey,
+    pub content_hash: [u8; 32],
+    pub version: u32,
+}
 
-/// Singleton protocol configuration — seeds: ["moltlaunch"]
-#[account]
-#[derive(InitSpace)]
-pub struct ProtocolConfig {
-    pub admin: Pubkey,           // 32
-    pub revocation_nonce: u64,   // 8
-    pub total_agents: u64,       // 8
-    pub total_attestations: u64, // 8
-    pub paused: bool,            // 1
-    pub bump: u8,                // 1
+#[event]
+pub struct AgentProfileClosed {
+    pub agent: Pubkey,
+    pub version: u32,
 }
 
-/// Authority — one per authorized verifier. Seeds: ["authority", pubkey]
-#[account]
-#[derive(InitSpace)]
-pub struct Authority {
-    pub pubkey: Pubkey,                  // 32
-    pub authority_type: AuthorityType,   // 1
-    pub attestation_count: u64,          // 8
-    pub active: bool,                    // 1
-    pub added_by: Pubkey,                // 32
-    pub added_at: i64,                   // 8
-    pub bump: u8,                        // 1
+#[event]
+pub struct SessionKeyCreated {
+    pub agent: Pubkey,
+    pub session_key: Pubkey,
+    pub allowed_actions: u8,
+    pub expires_at: i64,
 }
 
-/// AgentIdentity — the composable signal hub. Seeds: ["agent", wallet]
-#[account]
-#[derive(InitSpace)]
-pub struct AgentIdentity {
-    pub wallet: Pubkey,              // 32
-    pub infra_type: InfraType,       // 1
-    pub has_economic_stake: bool,    // 1
-    pub has_hardware_binding: bool,  // 1
-    pub attestation_count: u8,       // 1
-    pub is_flagged: bool,            // 1
-    pub trust_score: u8,             // 1
-    pub last_verified: i64,          // 8
-    pub nonce: u64,                  // 8
-    pub registered_at: i64,          // 8
-    #[max_len(32)]
-    pub name: String,                // 4 + 32
-    pub bump: u8,                    // 1
+#[event]
+pub struct SessionKeyRevoked {
+    pub agent: Pubkey,
+    pub session_key: Pubkey,
 }
 
-/// Attestation — one per (agent, authority) pair.
-/// Seeds: ["attestation", agent_wallet, authority_pubkey]
-#[account]
-#[derive(InitSpace)]
-pub struct Attestation {
-    pub agent: Pubkey,                       // 32
-    pub authority: Pubkey,                   // 32
-    pub authority_type: AuthorityType,       // 1
-    pub signal_contributed: SignalType,       // 1
-    pub attestation_hash: [u8; 32],          // 32
-    pub tee_quote: Option<[u8; 32]>,         // 1 + 32
-    pub created_at: i64,                     // 8
-    pub expires_at: i64,                     // 8
-    pub revoked: bool,                       // 1
-    pub bump: u8,                            // 1
+#[event]
+pub struct OperatorCreated {
+    pub operator: Pubkey,
+    pub name: String,
+}
+
+#[event]
+pub struct OperatorLinked {
+    pub agent: Pubkey,
+    pub operator: Pubkey,
+}
+
+#[event]
+pub struct OperatorUnlinked {
+    pub agent: Pubkey,
+    pub operator: Pubkey,
+}
+
+#[event]
+pub struct OperatorAttested {
+    pub operator: Pubkey,
+    pub authority: Pubkey,
+    pub signal_type: SignalType,
+}
+
+#[event]
+pub struct OperatorAttestationRevoked {
+    pub operator: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct OperatorFlagged {
+    pub operator: Pubkey,
+    pub authority: Pubkey,
+    pub reason_hash: [u8; 32],
+}
+
+#[event]
+pub struct OperatorUnflagged {
+    pub operator: Pubkey,
+}
+
+#[event]
+pub struct AgentTransferProposed {
+    pub agent: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+}
+
+#[event]
+pub struct AgentTransferred {
+    pub agent: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub signals_reset: bool,
+    pub live_attestations: u32,
+    pub recovery_keys_cleared: bool,
+}
+
+#[event]
+pub struct EvmAddressLinked {
+    pub agent: Pubkey,
+    pub evm_address: [u8; 20],
+}
+
+#[event]
+pub struct EvmAddressUnlinked {
+    pub agent: Pubkey,
+    pub evm_address: [u8; 20],
+}
+
+#[event]
+pub struct WalletLinked {
+    pub agent: Pubkey,
+    pub wallet: Pubkey,
+}
+
+#[event]
+pub struct WalletUnlinked {
+    pub agent: Pubkey,
+    pub wallet: Pubkey,
+}
+
+#[event]
+pub struct AttestationSubmitted {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub signal_type: SignalType,
+    pub pending: bool,
+}
+
+#[event]
+pub struct SignedAttestationSubmitted {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub relayer: Pubkey,
+    pub signal_type: SignalType,
+    pub nonce: u64,
+    pub pending: bool,
+}
+
+#[event]
+pub struct SchemaRegistered {
+    pub id: u32,
+    pub name: String,
+    pub layout_hash: [u8; 32],
+    pub weight: u8,
+}
+
+#[event]
+pub struct SchemaAttestationSubmitted {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub schema_id: u32,
+    pub weight: u8,
+    pub pending: bool,
+}
+
+#[event]
+pub struct EvidenceAttached {
+    pub attestation: Pubkey,
+    pub authority: Pubkey,
+    pub uri: String,
+    pub hash_algorithm: HashAlgorithm,
+    pub content_hash: [u8; 32],
+}
+
+#[event]
+pub struct EvidenceClosed {
+    pub attestation: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct AttestationBatchSubmitted {
+    pub authority: Pubkey,
+    pub signal_type: SignalType,
+    pub created: u32,
+    pub skipped: Vec<BatchSkip>,
+}
+
+#[event]
+pub struct AttestationBatchPosted {
+    pub batch: Pubkey,
+    pub authority: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct BatchAttestationClaimed {
+    pub batch: Pubkey,
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub signal_type: SignalType,
+    pub pending: bool,
+}
+
+#[event]
+pub struct AttestationBatchRevoked {
+    pub batch: Pubkey,
+    pub authority: Pubkey,
+    pub reason: RevocationReason,
+    pub reason_hash: [u8; 32],
+    pub revoked_at: i64,
+}
+
+#[event]
+pub struct RevokedBatchClaimClosed {
+    pub batch: Pubkey,
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub rent_recipient: Pubkey,
+}
+
+#[event]
+pub struct EnclaveMeasurementRegistered {
+    pub vendor: TeeVendor,
+    pub kind: MeasurementKind,
+    pub measurement: [u8; 32],
+}
+
+#[event]
+pub struct EnclaveMeasurementRevoked {
+    pub measurement: [u8; 32],
 }
 
-// =============================================================================
-// Enums
-// =============================================================================
+#[event]
+pub struct AttestationChallenged {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub challenger: Pubkey,
+    pub evidence_hash: [u8; 32],
+}
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
-pub enum AuthorityType {
-    Single,
-    MultisigMember,
-    OracleOperator,
-    NCNValidator,
+#[event]
+pub struct ChallengeResolved {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub challenger: Pubkey,
+    pub upheld: bool,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
-pub enum InfraType {
-    Unknown,
-    Cloud,
-    TEE,
-    DePIN,
+#[event]
+pub struct AttestationSuperseded {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub signal_type: SignalType,
+    pub previous: Pubkey,
+    pub attestation: Pubkey,
+    pub sequence: u32,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
-pub enum SignalType {
-    InfraCloud,
-    InfraTEE,
-    InfraDePIN,
-    EconomicStake,
-    HardwareBinding,
-    General,
+#[event]
+pub struct AttestationAccepted {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub signal_type: SignalType,
 }
 
-// =============================================================================
-// Events
-// =============================================================================
+#[event]
+pub struct AttestationRejected {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub signal_type: SignalType,
+}
 
 #[event]
-pub struct AgentRegistered {
-    pub wallet: Pubkey,
-    pub name: String,
+pub struct AttestationRenewed {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub previous_hash: [u8; 32],
+    pub attestation_hash: [u8; 32],
+    pub expires_at: i64,
+    pub renewal_count: u32,
 }
 
 #[event]
-pub struct AttestationSubmitted {
+pub struct ExpiredAttestationClosed {
     pub agent: Pubkey,
     pub authority: Pubkey,
-    pub signal_type: SignalType,
+    pub rent_recipient: Pubkey,
+    pub cranker: Pubkey,
+    pub cranker_tip: u64,
 }
 
 #[event]
 pub struct AttestationRevoked {
     pub agent: Pubkey,
     pub authority: Pubkey,
+    pub reason: RevocationReason,
+    pub reason_hash: [u8; 32],
+    pub revoked_at: i64,
 }
 
 #[event]
@@ -845,4 +6082,915 @@ pub enum MoltError {
 
     #[msg("Attestation is not revoked")]
     AttestationNotRevoked,
+
+    #[msg("Agent still has attestations that are not revoked")]
+    AgentHasLiveAttestations,
+
+    #[msg("Name must be 3-32 characters of a-z, 0-9, '-' or '_', without leading, trailing or repeated separators")]
+    InvalidName,
+
+    #[msg("Agent was renamed too recently")]
+    RenameCooldown,
+
+    #[msg("Profile field exceeds its maximum length or count")]
+    ProfileFieldTooLong,
+
+    #[msg("Session key action set is empty or contains unknown actions")]
+    InvalidSessionActions,
+
+    #[msg("Session key has expired")]
+    SessionKeyExpired,
+
+    #[msg("Session key lifetime exceeds the maximum")]
+    SessionDurationTooLong,
+
+    #[msg("Agent is linked to an operator")]
+    AgentLinkedToOperator,
+
+    #[msg("Agent is not linked to this operator")]
+    OperatorMismatch,
+
+    #[msg("Operator is flagged")]
+    OperatorFlagged,
+
+    #[msg("Linked agent's Operator account must be passed to refresh")]
+    OperatorAccountMissing,
+
+    #[msg("Transfer target must differ from the current owner")]
+    InvalidTransferTarget,
+
+    #[msg("No matching pending transfer")]
+    NoPendingTransfer,
+
+    #[msg("Liveness window cannot be negative")]
+    InvalidLivenessWindow,
+
+    #[msg("Instruction not allowed in the agent's current status")]
+    InvalidAgentStatus,
+
+    #[msg("Agent is frozen")]
+    AgentFrozen,
+
+    #[msg("Agent is not frozen")]
+    AgentNotFrozen,
+
+    #[msg("Guardian thaw delay has not elapsed")]
+    ThawTooEarly,
+
+    #[msg("EVM signature does not recover to the given address")]
+    InvalidEvmSignature,
+
+    #[msg("Cannot link the agent's own wallet or owner")]
+    InvalidWalletLink,
+
+    #[msg("Attestation is not pending")]
+    AttestationNotPending,
+
+    #[msg("Attestation is revoked")]
+    AttestationRevoked,
+
+    #[msg("Account is not a valid attestation PDA")]
+    InvalidAttestationAccount,
+
+    #[msg("Attestation already uses the current layout")]
+    AttestationUpToDate,
+
+    #[msg("Account is not a valid PDA of the type being migrated")]
+    InvalidMigrationAccount,
+
+    #[msg("Account already uses the current layout")]
+    AccountUpToDate,
+
+    #[msg("Attestation has not passed its expiry grace period")]
+    AttestationNotExpired,
+
+    #[msg("Agent account is required for an unrevoked attestation")]
+    AgentAccountMissing,
+
+    #[msg("Grace period cannot be negative and the cranker tip must not exceed the maximum")]
+    InvalidCrankPolicy,
+
+    #[msg("Previous instruction is not a matching Ed25519 signature check")]
+    InvalidEd25519Instruction,
+
+    #[msg("Signed attestation nonce has already been used")]
+    SignedNonceReused,
+
+    #[msg("Schema id is reserved, or its name, lifetime or authority list is invalid")]
+    InvalidSchema,
+
+    #[msg("Schema is not active")]
+    SchemaInactive,
+
+    #[msg("Authority is not allowed to attest this schema")]
+    AuthorityNotAllowedForSchema,
+
+    #[msg("Evidence field exceeds its maximum length")]
+    EvidenceFieldTooLong,
+
+    #[msg("Evidence needs a URI or an inline payload")]
+    EvidenceEmpty,
+
+    #[msg("Batch must pair every hash with an agent and attestation account, up to the maximum")]
+    InvalidBatch,
+
+    #[msg("Every leaf of this batch has already been claimed")]
+    BatchFullyClaimed,
+
+    #[msg("Merkle proof does not match the batch root")]
+    InvalidMerkleProof,
+
+    #[msg("InfraTEE attestations need a quote digest and a registered enclave measurement")]
+    TeeQuoteRequired,
+
+    #[msg("Enclave measurement is revoked")]
+    EnclaveMeasurementInactive,
+
+    #[msg("TEE quote was not produced under the cited enclave measurement")]
+    TeeQuoteMismatch,
+
+    #[msg("Attestation already cites an enclave measurement")]
+    EnclaveMeasurementBound,
+
+    #[msg("Challenge period cannot be negative or exceed the maximum")]
+    InvalidChallengePeriod,
+
+    #[msg("Attestation is past its challenge window")]
+    ChallengeWindowClosed,
+
+    #[msg("Challenge is already resolved")]
+    ChallengeResolved,
+
+    #[msg("Challenge is unresolved and its attestation still exists")]
+    ChallengeOpen,
+
+    #[msg("Superseded attestation must be for the same signal and not already superseded")]
+    InvalidSupersession,
+
+    #[msg("Agent profile must be closed with close_agent_profile first")]
+    AgentHasProfile,
+
+    #[msg("Challenged attestation has since been closed and recreated")]
+    ChallengeStale,
+
+    #[msg("Signed attestation nonce is too far behind the authority's highest nonce")]
+    SignedNonceTooOld,
+}
+
+// =============================================================================
+// Signer Authorization
+// =============================================================================
+
+/// Guardian must wait this long after a freeze before thawing (72 hours), so
+/// an attacker who also holds the guardian key cannot undo a freeze quickly.
+/// The admin can thaw at any time.
+pub const GUARDIAN_THAW_DELAY: i64 = 72 * 60 * 60;
+
+/// A new guardian (or removal of the current one) takes effect only after
+/// this long (72 hours), giving the real owner time to notice and freeze.
+pub const GUARDIAN_CHANGE_DELAY: i64 = 72 * 60 * 60;
+
+/// Accepts `signer` if it is the agent's owner, or a session key of that
+/// agent that is unexpired and allows `action`. Frozen agents accept neither.
+pub fn authorize_agent_signer(
+    agent: &AgentIdentity,
+    signer: &Pubkey,
+    session: Option<&SessionKey>,
+    action: u8,
+    now: i64,
+) -> Result<()> {
+    require!(!agent.is_frozen, MoltError::AgentFrozen);
+    if *signer == agent.owner {
+        return Ok(());
+    }
+    match session {
+        Some(session) if session.authorizes(agent, signer, action, now) => Ok(()),
+        _ => err!(MoltError::Unauthorized),
+    }
+}
+
+// =============================================================================
+// Name Normalization
+// =============================================================================
+
+/// Minimum time between renames of the same agent (7 days).
+pub const RENAME_COOLDOWN: i64 = 7 * 24 * 60 * 60;
+
+/// Validates an agent name and folds it to its canonical lowercase form.
+///
+/// Only ASCII letters, digits, '-' and '_' are accepted, which rules out
+/// control characters, whitespace and non-Latin homoglyphs outright.
+pub fn normalize_agent_name(name: &str) -> Result<String> {
+    require!(name.len() <= 32, MoltError::NameTooLong);
+    require!(name.len() >= 3, MoltError::InvalidName);
+
+    let mut normalized = String::with_capacity(name.len());
+    let mut prev_separator = true; // rejects a leading separator
+    for c in name.chars() {
+        let c = c.to_ascii_lowercase();
+        let separator = c == '-' || c == '_';
+        require!(
+            c.is_ascii_lowercase() || c.is_ascii_digit() || separator,
+            MoltError::InvalidName
+        );
+        require!(!(separator && prev_separator), MoltError::InvalidName);
+        prev_separator = separator;
+        normalized.push(c);
+    }
+    require!(!prev_separator, MoltError::InvalidName);
+
+    Ok(normalized)
+}
+
+/// NameRecord seed for a name: its normalized form with visually confusable
+/// characters folded together ("0" → "o", "1" → "l", "rn" → "m", "vv" → "w",
+/// "_" → "-"). Returns an empty seed for invalid names; the instruction
+/// rejects those before anything is written.
+pub fn name_seed(name: &str) -> Vec<u8> {
+    let normalized = match normalize_agent_name(name) {
+        Ok(normalized) => normalized,
+        Err(_) => return Vec::new(),
+    };
+
+    let bytes = normalized.as_bytes();
+    let mut seed = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let pair = bytes.get(i..i + 2);
+        if pair == Some(b"rn") {
+            seed.push(b'm');
+            i += 2;
+            continue;
+        }
+        if pair == Some(b"vv") {
+            seed.push(b'w');
+            i += 2;
+            continue;
+        }
+        seed.push(match bytes[i] {
+            b'0' => b'o',
+            b'1' => b'l',
+            b'_' => b'-',
+            b => b,
+        });
+        i += 1;
+    }
+    seed
+}
+
+// =============================================================================
+// EVM Address Proofs
+// =============================================================================
+
+/// The challenge an EVM wallet signs with personal_sign to link itself.
+/// Binds both the agent and its current owner, so a signature cannot be
+/// replayed after an ownership transfer. Links made before a transfer
+/// persist but are stale; see LinkedAddress::is_current.
+pub fn evm_link_message(agent: &Pubkey, owner: &Pubkey, evm_address: &[u8; 20]) -> String {
+    let mut hex = String::with_capacity(40);
+    for byte in evm_address {
+        hex.push_str(&format!("{:02x}", byte));
+    }
+    format!(
+        "MoltLaunch: link EVM address 0x{} to agent {} owned by {}",
+        hex, agent, owner
+    )
+}
+
+/// Recovers the EVM address that produced an EIP-191 signature over `message`.
+/// Accepts recovery ids as 0/1 or Ethereum-style 27/28.
+pub fn recover_evm_signer(message: &[u8], signature: &[u8; 64], recovery_id: u8) -> Result<[u8; 20]> {
+    let prefix = format!("\x19Ethereum Signed Message:\n{}", message.len());
+    let digest = solana_keccak_hasher::hashv(&[prefix.as_bytes(), message]);
+
+    let recovery_id = if recovery_id >= 27 { recovery_id - 27 } else { recovery_id };
+    let pubkey = solana_secp256k1_recover::secp256k1_recover(&digest.to_bytes(), recovery_id, signature)
+        .map_err(|_| MoltError::InvalidEvmSignature)?;
+
+    let hash = solana_keccak_hasher::hash(&pubkey.to_bytes()).to_bytes();
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Ok(address)
+}
+
+// =============================================================================
+// Merkle Batches
+// =============================================================================
+
+/// Leaf of an AttestationBatch tree. Leaves and nodes are domain-separated
+/// (0x00 / 0x01 prefixes) so an inner node cannot be passed off as a leaf.
+pub fn batch_leaf(
+    agent: &Pubkey,
+    signal_type: &SignalType,
+    attestation_hash: &[u8; 32],
+    expires_at: i64,
+) -> [u8; 32] {
+    solana_keccak_hasher::hashv(&[
+        &[0x00],
+        agent.as_ref(),
+        &[signal_type.clone() as u8],
+        attestation_hash,
+        &expires_at.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Verifies `leaf` against `root`. Sibling pairs are hashed in sorted order,
+/// so proofs carry no left/right flags.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let node = proof.iter().fold(leaf, |node, sibling| {
+        let (a, b) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        solana_keccak_hasher::hashv(&[&[0x01], &a, &b]).to_bytes()
+    });
+    node == *root
+}
+
+// =============================================================================
+// Signed Attestations
+// =============================================================================
+
+/// The payload an authority signs off-chain for submit_signed_attestation.
+/// Prefixed with the program id so signatures cannot be replayed against
+/// another deployment.
+#[allow(clippy::too_many_arguments)]
+pub fn signed_attestation_message(
+    agent: &Pubkey,
+    signal_type: &SignalType,
+    attestation_hash: &[u8; 32],
+    tee_quote: &Option<TeeQuote>,
+    expires_at: i64,
+    index: u8,
+    previous: &Option<Pubkey>,
+    nonce: u64,
+) -> Vec<u8> {
+    let mut message = b"MoltLaunch signed attestation v1".to_vec();
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(agent.as_ref());
+    // Borsh encoding of these types cannot fail when writing to a Vec
+    signal_type.serialize(&mut message).unwrap();
+    message.extend_from_slice(attestation_hash);
+    tee_quote.serialize(&mut message).unwrap();
+    message.extend_from_slice(&expires_at.to_le_bytes());
+    message.push(index);
+    previous.serialize(&mut message).unwrap();
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message
+}
+
+/// How far below an authority's highest relayed nonce a signed attestation
+/// may still land, so relayers need not submit in signing order.
+pub const SIGNED_NONCE_WINDOW: u64 = 128;
+
+impl Authority {
+    /// Marks `nonce` used. Nonces above the highest so far advance it, leaving
+    /// any skipped ones open; nonces inside the window below it are accepted
+    /// once each. Legacy authorities read with no open nonces, so nothing
+    /// relayed before the window existed can be replayed.
+    pub fn consume_signed_nonce(&mut self, nonce: u64) -> Result<()> {
+        let last = self.last_signed_nonce;
+        if nonce > last {
+            let shift = nonce - last;
+            let carried = if shift < SIGNED_NONCE_WINDOW {
+                self.open_signed_nonces << shift
+            } else {
+                0
+            };
+            let skipped = if shift - 1 < SIGNED_NONCE_WINDOW {
+                (1u128 << (shift - 1)) - 1
+            } else {
+                u128::MAX
+            };
+            self.open_signed_nonces = carried | skipped;
+            self.last_signed_nonce = nonce;
+            return Ok(());
+        }
+
+        require!(nonce != last, MoltError::SignedNonceReused);
+        let bit = last - 1 - nonce;
+        require!(bit < SIGNED_NONCE_WINDOW, MoltError::SignedNonceTooOld);
+        let mask = 1u128 << bit;
+        require!(self.open_signed_nonces & mask != 0, MoltError::SignedNonceReused);
+        self.open_signed_nonces &= !mask;
+        Ok(())
+    }
+}
+
+/// Checks that `ix` is an Ed25519 program instruction verifying exactly one
+/// signature by `signer` over `message`, with all data inline in `ix`.
+pub fn verify_ed25519_instruction(
+    ix: &anchor_lang::solana_program::instruction::Instruction,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    // Layout: [count u8, padding u8] then per signature seven u16 offsets:
+    // sig, sig_ix, pubkey, pubkey_ix, msg, msg_len, msg_ix
+    const HEADER: usize = 2;
+    const OFFSETS: usize = 14;
+    const INLINE: u16 = u16::MAX;
+
+    let data = &ix.data;
+    require!(
+        ix.program_id == solana_sdk_ids::ed25519_program::ID
+            && data.len() >= HEADER + OFFSETS
+            && data[0] == 1,
+        MoltError::InvalidEd25519Instruction
+    );
+
+    let read_u16 = |i: usize| u16::from_le_bytes([data[HEADER + 2 * i], data[HEADER + 2 * i + 1]]);
+    let pubkey_offset = read_u16(2) as usize;
+    let msg_offset = read_u16(4) as usize;
+    let msg_len = read_u16(5) as usize;
+    require!(
+        read_u16(1) == INLINE && read_u16(3) == INLINE && read_u16(6) == INLINE,
+        MoltError::InvalidEd25519Instruction
+    );
+
+    let signed_pubkey = data.get(pubkey_offset..pubkey_offset + 32);
+    let signed_message = data.get(msg_offset..msg_offset + msg_len);
+    require!(
+        signed_pubkey == Some(signer.as_ref()) && signed_message == Some(message),
+        MoltError::InvalidEd25519Instruction
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::instruction::Instruction;
+
+    // -------------------------------------------------------------------------
+    // Signed Attestations
+    // -------------------------------------------------------------------------
+
+    const SIG_OFFSET: u16 = 16;
+    const PUBKEY_OFFSET: u16 = SIG_OFFSET + 64;
+    const MSG_OFFSET: u16 = PUBKEY_OFFSET + 32;
+
+    /// Ed25519 program instruction carrying one signature, with the
+    /// signature, pubkey and message all inline.
+    fn ed25519_ix(signer: &Pubkey, message: &[u8]) -> Instruction {
+        let mut data = vec![1u8, 0];
+        for offset in [
+            SIG_OFFSET,
+            u16::MAX,
+            PUBKEY_OFFSET,
+            u16::MAX,
+            MSG_OFFSET,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(&[7u8; 64]);
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(message);
+        Instruction {
+            program_id: solana_sdk_ids::ed25519_program::ID,
+            accounts: vec![],
+            data,
+        }
+    }
+
+    fn set_offset(ix: &mut Instruction, slot: usize, value: u16) {
+        ix.data[2 + 2 * slot..4 + 2 * slot].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn message() -> Vec<u8> {
+        signed_attestation_message(
+            &Pubkey::new_unique(),
+            &SignalType::InfraCloud,
+            &[3; 32],
+            &None,
+            1_900_000_000,
+            0,
+            &None,
+            1,
+        )
+    }
+
+    #[test]
+    fn ed25519_accepts_inline_signature_by_signer() {
+        let signer = Pubkey::new_unique();
+        let message = message();
+        assert!(verify_ed25519_instruction(&ed25519_ix(&signer, &message), &signer, &message).is_ok());
+    }
+
+    #[test]
+    fn ed25519_rejects_other_signer_or_message() {
+        let signer = Pubkey::new_unique();
+        let message = message();
+        let ix = ed25519_ix(&signer, &message);
+        assert!(verify_ed25519_instruction(&ix, &Pubkey::new_unique(), &message).is_err());
+        assert!(verify_ed25519_instruction(&ix, &signer, &message[1..]).is_err());
+    }
+
+    #[test]
+    fn ed25519_rejects_wrong_program_id() {
+        let signer = Pubkey::new_unique();
+        let message = message();
+        let mut ix = ed25519_ix(&signer, &message);
+        ix.program_id = solana_sdk_ids::secp256k1_program::ID;
+        assert!(verify_ed25519_instruction(&ix, &signer, &message).is_err());
+    }
+
+    #[test]
+    fn ed25519_rejects_signature_count_other_than_one() {
+        let signer = Pubkey::new_unique();
+        let message = message();
+        for count in [0u8, 2] {
+            let mut ix = ed25519_ix(&signer, &message);
+            ix.data[0] = count;
+            assert!(verify_ed25519_instruction(&ix, &signer, &message).is_err());
+        }
+    }
+
+    #[test]
+    fn ed25519_rejects_data_in_another_instruction() {
+        let signer = Pubkey::new_unique();
+        let message = message();
+        // Signature, pubkey and message instruction indexes
+        for slot in [1, 3, 6] {
+            let mut ix = ed25519_ix(&signer, &message);
+            set_offset(&mut ix, slot, 0);
+            assert!(verify_ed25519_instruction(&ix, &signer, &message).is_err());
+        }
+    }
+
+    #[test]
+    fn ed25519_rejects_offsets_outside_instruction() {
+        let signer = Pubkey::new_unique();
+        let message = message();
+        let len = ed25519_ix(&signer, &message).data.len() as u16;
+
+        let mut ix = ed25519_ix(&signer, &message);
+        set_offset(&mut ix, 2, len - 16);
+        assert!(verify_ed25519_instruction(&ix, &signer, &message).is_err());
+
+        let mut ix = ed25519_ix(&signer, &message);
+        set_offset(&mut ix, 4, u16::MAX - 1);
+        assert!(verify_ed25519_instruction(&ix, &signer, &message).is_err());
+
+        let mut ix = ed25519_ix(&signer, &message);
+        set_offset(&mut ix, 5, message.len() as u16 + 1);
+        assert!(verify_ed25519_instruction(&ix, &signer, &message).is_err());
+
+        let mut ix = ed25519_ix(&signer, &message);
+        ix.data.truncate(8);
+        assert!(verify_ed25519_instruction(&ix, &signer, &message).is_err());
+    }
+
+    #[test]
+    fn signed_message_binds_every_field() {
+        let agent = Pubkey::new_unique();
+        let build = |index: u8, previous: Option<Pubkey>, nonce: u64| {
+            signed_attestation_message(
+                &agent,
+                &SignalType::InfraCloud,
+                &[3; 32],
+                &None,
+                1_900_000_000,
+                index,
+                &previous,
+                nonce,
+            )
+        };
+        let base = build(0, None, 1);
+        assert!(base.starts_with(b"MoltLaunch signed attestation v1"));
+        assert_ne!(base, build(1, None, 1));
+        assert_ne!(base, build(0, Some(Pubkey::new_unique()), 1));
+        assert_ne!(base, build(0, None, 2));
+    }
+
+    #[test]
+    fn signed_nonces_accept_out_of_order_once() {
+        let mut authority = blank::<Authority>();
+        authority.consume_signed_nonce(5).unwrap();
+        authority.consume_signed_nonce(3).unwrap();
+        authority.consume_signed_nonce(7).unwrap();
+        authority.consume_signed_nonce(1).unwrap();
+        authority.consume_signed_nonce(6).unwrap();
+        for used in [0, 1, 3, 5, 6, 7] {
+            assert!(authority.consume_signed_nonce(used).is_err(), "nonce {used} replayed");
+        }
+        authority.consume_signed_nonce(2).unwrap();
+        authority.consume_signed_nonce(4).unwrap();
+        assert_eq!(authority.open_signed_nonces, 0);
+    }
+
+    #[test]
+    fn signed_nonces_expire_behind_the_window() {
+        let mut authority = blank::<Authority>();
+        authority.consume_signed_nonce(1).unwrap();
+        authority.consume_signed_nonce(2 + SIGNED_NONCE_WINDOW).unwrap();
+        // Nonce 2 is the oldest skipped nonce still inside the window
+        authority.consume_signed_nonce(2).unwrap();
+        authority.consume_signed_nonce(1000).unwrap();
+        assert!(authority.consume_signed_nonce(3).is_err());
+        authority.consume_signed_nonce(1000 - SIGNED_NONCE_WINDOW).unwrap();
+    }
+
+    #[test]
+    fn legacy_authority_has_no_open_signed_nonces() {
+        let mut authority = blank::<Authority>();
+        authority.last_signed_nonce = 10;
+        assert!(authority.consume_signed_nonce(9).is_err());
+        authority.consume_signed_nonce(11).unwrap();
+    }
+
+    // -------------------------------------------------------------------------
+    // Name Normalization
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn normalize_lowercases_valid_names() {
+        assert_eq!(normalize_agent_name("MoltBot-7").unwrap(), "moltbot-7");
+        assert_eq!(normalize_agent_name("a_b").unwrap(), "a_b");
+        assert_eq!(normalize_agent_name(&"x".repeat(32)).unwrap(), "x".repeat(32));
+    }
+
+    #[test]
+    fn normalize_rejects_bad_lengths_and_characters() {
+        assert!(normalize_agent_name("ab").is_err());
+        assert!(normalize_agent_name(&"x".repeat(33)).is_err());
+        assert!(normalize_agent_name("molt bot").is_err());
+        assert!(normalize_agent_name("molt.bot").is_err());
+        // Cyrillic 'а' in place of Latin 'a'
+        assert!(normalize_agent_name("p\u{430}ypal").is_err());
+        assert!(normalize_agent_name("molt\u{200b}bot").is_err());
+    }
+
+    #[test]
+    fn normalize_rejects_separator_edge_cases() {
+        for name in ["-abc", "_abc", "abc-", "abc_", "a--b", "a__b", "a-_b", "a_-b", "---"] {
+            assert!(normalize_agent_name(name).is_err(), "{name}");
+        }
+        assert!(normalize_agent_name("a-b_c").is_ok());
+    }
+
+    #[test]
+    fn name_seed_folds_confusables_together() {
+        for (a, b) in [
+            ("paypal", "PayPa1"),
+            ("google", "g00gle"),
+            ("moon", "rnoon"),
+            ("wave", "vvave"),
+            ("my-agent", "my_agent"),
+            ("modern", "rnodem"),
+        ] {
+            assert_eq!(name_seed(a), name_seed(b), "{a} / {b}");
+        }
+    }
+
+    #[test]
+    fn name_seed_keeps_distinct_names_apart() {
+        assert_ne!(name_seed("alpha"), name_seed("alpho"));
+        assert_ne!(name_seed("agent-1"), name_seed("agent-2"));
+        assert_ne!(name_seed("abc"), name_seed("abcd"));
+    }
+
+    #[test]
+    fn name_seed_is_empty_for_invalid_names() {
+        assert!(name_seed("ab").is_empty());
+        assert!(name_seed("-abc").is_empty());
+        assert!(name_seed("a b c").is_empty());
+    }
+
+    // -------------------------------------------------------------------------
+    // EVM Address Proofs
+    // -------------------------------------------------------------------------
+
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        let mut out = [0u8; N];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    // web3.js accounts.sign("Some data", 0x4c0883a6...f362318)
+    const EIP191_MESSAGE: &[u8] = b"Some data";
+    const EIP191_SIGNATURE: &str = "b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd\
+                                    6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a029";
+    const EIP191_ADDRESS: &str = "2c7536e3605d9c16a7a3d7b1898e529396a65c23";
+
+    #[test]
+    fn recover_evm_signer_matches_eip191_vector() {
+        let signature = hex::<64>(EIP191_SIGNATURE);
+        let address = hex::<20>(EIP191_ADDRESS);
+        assert_eq!(recover_evm_signer(EIP191_MESSAGE, &signature, 28).unwrap(), address);
+        assert_eq!(recover_evm_signer(EIP191_MESSAGE, &signature, 1).unwrap(), address);
+    }
+
+    #[test]
+    fn recover_evm_signer_differs_for_other_message_or_recovery_id() {
+        let signature = hex::<64>(EIP191_SIGNATURE);
+        let address = hex::<20>(EIP191_ADDRESS);
+        assert_ne!(recover_evm_signer(b"Some datb", &signature, 28).ok(), Some(address));
+        assert_ne!(recover_evm_signer(EIP191_MESSAGE, &signature, 27).ok(), Some(address));
+    }
+
+    // -------------------------------------------------------------------------
+    // Merkle Batches
+    // -------------------------------------------------------------------------
+
+    fn node(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let (a, b) = if a <= b { (a, b) } else { (b, a) };
+        solana_keccak_hasher::hashv(&[&[0x01], &a, &b]).to_bytes()
+    }
+
+    /// Four-leaf tree; returns the leaves and the root.
+    fn tree() -> ([[u8; 32]; 4], [u8; 32]) {
+        let leaves = [0u8, 1, 2, 3].map(|i| {
+            batch_leaf(&Pubkey::new_unique(), &SignalType::InfraCloud, &[i; 32], 1_900_000_000)
+        });
+        let root = node(node(leaves[0], leaves[1]), node(leaves[2], leaves[3]));
+        (leaves, root)
+    }
+
+    #[test]
+    fn merkle_proof_accepts_every_leaf() {
+        let (leaves, root) = tree();
+        let left = node(leaves[0], leaves[1]);
+        let right = node(leaves[2], leaves[3]);
+        assert!(verify_merkle_proof(&[leaves[1], right], &root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[0], right], &root, leaves[1]));
+        assert!(verify_merkle_proof(&[leaves[3], left], &root, leaves[2]));
+        assert!(verify_merkle_proof(&[leaves[2], left], &root, leaves[3]));
+    }
+
+    #[test]
+    fn merkle_proof_rejects_bad_proof() {
+        let (leaves, root) = tree();
+        let right = node(leaves[2], leaves[3]);
+        let mut tampered = right;
+        tampered[0] ^= 1;
+        assert!(!verify_merkle_proof(&[leaves[1], tampered], &root, leaves[0]));
+        assert!(!verify_merkle_proof(&[leaves[1]], &root, leaves[0]));
+        assert!(!verify_merkle_proof(&[], &root, leaves[0]));
+    }
+
+    #[test]
+    fn merkle_proof_rejects_swapped_siblings() {
+        let (leaves, root) = tree();
+        let right = node(leaves[2], leaves[3]);
+        // Siblings out of level order
+        assert!(!verify_merkle_proof(&[right, leaves[1]], &root, leaves[0]));
+        // A leaf's proof does not prove its sibling
+        assert!(!verify_merkle_proof(&[leaves[1], right], &root, leaves[1]));
+    }
+
+    #[test]
+    fn batch_leaf_binds_every_field() {
+        let agent = Pubkey::new_unique();
+        let base = batch_leaf(&agent, &SignalType::InfraCloud, &[1; 32], 100);
+        assert_ne!(base, batch_leaf(&Pubkey::new_unique(), &SignalType::InfraCloud, &[1; 32], 100));
+        assert_ne!(base, batch_leaf(&agent, &SignalType::InfraTEE, &[1; 32], 100));
+        assert_ne!(base, batch_leaf(&agent, &SignalType::InfraCloud, &[2; 32], 100));
+        assert_ne!(base, batch_leaf(&agent, &SignalType::InfraCloud, &[1; 32], 101));
+    }
+
+    #[test]
+    fn batch_claims_are_addressed_by_their_batch() {
+        let agent = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let address = |attestation: &Attestation| {
+            Pubkey::find_program_address(
+                &[b"attestation", agent.as_ref(), authority.as_ref(), attestation.index_seed()],
+                &crate::ID,
+            )
+            .0
+        };
+
+        let mut direct = blank::<Attestation>();
+        direct.index = 1;
+        let mut claim = blank::<Attestation>();
+        claim.index = 1;
+        claim.batch = Pubkey::new_unique();
+        let mut other_claim = claim.clone();
+        other_claim.batch = Pubkey::new_unique();
+
+        assert_ne!(address(&direct), address(&claim));
+        assert_ne!(address(&claim), address(&other_claim));
+    }
+
+    // -------------------------------------------------------------------------
+    // Agent Lifecycle
+    // -------------------------------------------------------------------------
+
+    /// An account of type T with every field zeroed.
+    fn blank<T: AccountDeserialize + Space>() -> T {
+        T::try_deserialize_unchecked(&mut &vec![0u8; 8 + T::INIT_SPACE][..]).unwrap()
+    }
+
+    #[test]
+    fn transfer_clears_recovery_keys() {
+        let seller = Pubkey::new_unique();
+        let mut agent = blank::<AgentIdentity>();
+        agent.emergency_key = Some(seller);
+        agent.guardian = Some(seller);
+        agent.pending_guardian = Some(seller);
+        agent.guardian_change_at = 100;
+
+        assert!(agent.clear_recovery_keys());
+        assert_eq!(agent.emergency_key, None);
+        assert_eq!(agent.guardian, None);
+        assert_eq!(agent.pending_guardian, None);
+        assert_eq!(agent.guardian_change_at, 0);
+        assert!(!agent.clear_recovery_keys());
+    }
+
+    #[test]
+    fn unban_returns_retired_agents_to_retired() {
+        let mut agent = blank::<AgentIdentity>();
+        agent.transition(AgentStatus::Retired, 1);
+        agent.transition(AgentStatus::Banned, 2);
+        assert!(agent.status_after_unban() == AgentStatus::Retired);
+
+        let mut agent = blank::<AgentIdentity>();
+        agent.transition(AgentStatus::Active, 1);
+        agent.transition(AgentStatus::Banned, 2);
+        assert!(agent.status_after_unban() == AgentStatus::Suspended);
+    }
+
+    #[test]
+    fn tombstone_restores_flag_and_fraud_record() {
+        let mut tombstone = blank::<AgentTombstone>();
+        tombstone.was_flagged = true;
+        tombstone.fraud_revocations = 2;
+
+        let mut agent = blank::<AgentIdentity>();
+        tombstone.restore(&mut agent, 1);
+        assert_eq!(agent.fraud_revocations, 2);
+        assert!(agent.is_flagged);
+        assert!(agent.status == AgentStatus::Suspended);
+    }
+
+    #[test]
+    fn tombstone_written_before_fraud_record_reads_as_zero() {
+        let mut tombstone = blank::<AgentTombstone>();
+        tombstone.close_count = 1;
+        let mut data = Vec::new();
+        tombstone.try_serialize(&mut data).unwrap();
+        data.truncate(data.len() - 4);
+
+        let record: AgentTombstone = try_deserialize_padded(&data).unwrap();
+        assert_eq!(record.close_count, 1);
+        assert_eq!(record.fraud_revocations, 0);
+    }
+
+    #[test]
+    fn controller_signal_reset_zeroes_trust_score() {
+        let mut agent = blank::<AgentIdentity>();
+        agent.infra_type = InfraType::TEE;
+        agent.has_economic_stake = true;
+        agent.has_hardware_binding = true;
+        agent.trust_score = 80;
+        agent.reset_controller_signals(500);
+
+        assert!(!agent.has_economic_stake && !agent.has_hardware_binding);
+        assert_eq!(agent.trust_score, 0);
+        assert_eq!(agent.signals_reset_at, 500);
+        // Signals not bound to the controller are left for refresh to rebuild
+        assert!(agent.infra_type == InfraType::TEE);
+    }
+
+    #[test]
+    fn agent_rent_returns_to_payer_or_legacy_wallet() {
+        let mut agent = blank::<AgentIdentity>();
+        agent.wallet = Pubkey::new_unique();
+        agent.owner = Pubkey::new_unique();
+        assert_eq!(agent.rent_recipient(), agent.wallet);
+
+        agent.payer = Pubkey::new_unique();
+        assert_eq!(agent.rent_recipient(), agent.payer);
+    }
+
+    // -------------------------------------------------------------------------
+    // Challenges
+    // -------------------------------------------------------------------------
+
+    fn attestation_data(created_at: i64) -> Vec<u8> {
+        let mut attestation = blank::<Attestation>();
+        attestation.created_at = created_at;
+        let mut data = Vec::new();
+        attestation.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn challenge_stays_live_for_the_attestation_challenged() {
+        let mut challenge = blank::<AttestationChallenge>();
+        challenge.attestation_created_at = 100;
+        assert!(!challenge.is_stale(&attestation_data(100)));
+    }
+
+    #[test]
+    fn challenge_goes_stale_when_attestation_closed_or_recreated() {
+        let mut challenge = blank::<AttestationChallenge>();
+        challenge.attestation_created_at = 100;
+        assert!(challenge.is_stale(&[]));
+        assert!(challenge.is_stale(&attestation_data(200)));
+        assert!(challenge.is_stale(&[0u8; 64]));
+    }
 }
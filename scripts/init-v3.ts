@@ -26,7 +26,20 @@ async function main() {
   const [configPda] = PublicKey.findProgramAddressSync([Buffer.from("moltlaunch")], PROGRAM_ID);
   console.log("Config PDA:", configPda.toBase58());
 
-  // 1. Initialize
+  // 1. Initialize (or grow a config written by an older layout)
+  if (await connection.getAccountInfo(configPda)) {
+    try {
+      await (program.account as any).protocolConfig.fetch(configPda);
+    } catch {
+      console.log("\nMigrating config to the current layout...");
+      const tx = await program.methods.migrateConfig()
+        .accountsPartial({
+          config: configPda, payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+        })
+        .signers([walletKeypair]).rpc();
+      console.log("✅ Config migrated! Tx:", tx);
+    }
+  }
   try {
     const configAccount = await (program.account as any).protocolConfig.fetch(configPda);
     console.log("\n✅ Protocol already initialized!");
@@ -36,7 +49,10 @@ async function main() {
   } catch (e) {
     console.log("\nInitializing protocol...");
     const tx = await program.methods.initialize()
-      .accountsPartial({ config: configPda, admin: walletKeypair.publicKey, systemProgram: SystemProgram.programId })
+      .accountsPartial({
+        config: configPda, admin: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      })
       .signers([walletKeypair]).rpc();
     console.log("✅ Initialized! Tx:", tx);
   }
@@ -47,6 +63,19 @@ async function main() {
   );
   console.log("\nAuthority PDA:", authorityPda.toBase58());
 
+  if (await connection.getAccountInfo(authorityPda)) {
+    try {
+      await (program.account as any).authority.fetch(authorityPda);
+    } catch {
+      console.log("Migrating authority to the current layout...");
+      const tx = await program.methods.migrateAuthority()
+        .accountsPartial({
+          authority: authorityPda, payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+        })
+        .signers([walletKeypair]).rpc();
+      console.log("✅ Authority migrated! Tx:", tx);
+    }
+  }
   try {
     const auth = await (program.account as any).authority.fetch(authorityPda);
     console.log("✅ Authority already registered! Type:", JSON.stringify(auth.authorityType), "Active:", auth.active);
@@ -56,15 +85,36 @@ async function main() {
       .accountsPartial({
         config: configPda, authority: authorityPda,
         authorityPubkey: walletKeypair.publicKey, admin: walletKeypair.publicKey,
-        systemProgram: SystemProgram.programId
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
       }).signers([walletKeypair]).rpc();
     console.log("✅ Authority added! Tx:", tx);
   }
 
+  // 2b. Register the built-in signal schemas
+  const builtins = ["infraCloud", "infraTee", "infraDePin", "economicStake", "hardwareBinding", "general"];
+  for (const [i, signal] of builtins.entries()) {
+    const id = Buffer.alloc(4);
+    id.writeUInt32LE(i + 1);
+    const [schemaPda] = PublicKey.findProgramAddressSync([Buffer.from("schema"), id], PROGRAM_ID);
+    if (await connection.getAccountInfo(schemaPda)) continue;
+    const tx = await program.methods.registerBuiltinSchema({ [signal]: {} })
+      .accountsPartial({
+        config: configPda, schema: schemaPda, admin: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([walletKeypair]).rpc();
+    console.log(`✅ Built-in schema ${i + 1} (${signal}) registered! Tx:`, tx);
+  }
+
   // 3. Register agent
   const [agentPda] = PublicKey.findProgramAddressSync(
     [Buffer.from("agent"), walletKeypair.publicKey.toBytes()], PROGRAM_ID
   );
+  const [namePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("name"), Buffer.from("moltlaunch-agent")], PROGRAM_ID
+  );
+  const [tombstonePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("tombstone"), walletKeypair.publicKey.toBytes()], PROGRAM_ID
+  );
   console.log("\nAgent PDA:", agentPda.toBase58());
 
   try {
@@ -74,8 +124,9 @@ async function main() {
     console.log("Registering agent (moltlaunch-agent)...");
     const tx = await program.methods.registerAgent("moltlaunch-agent")
       .accountsPartial({
-        config: configPda, agent: agentPda,
-        wallet: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+        config: configPda, agent: agentPda, nameRecord: namePda, tombstone: tombstonePda,
+        wallet: walletKeypair.publicKey, payer: walletKeypair.publicKey,
+        systemProgram: SystemProgram.programId
       }).signers([walletKeypair]).rpc();
     console.log("✅ Agent registered! Tx:", tx);
   }
@@ -97,11 +148,11 @@ async function main() {
     Buffer.from("moltlaunch-v3-self-attest").forEach((b, i) => { if (i < 32) hash[i] = b; });
 
     const tx = await program.methods
-      .submitAttestation({ infraCloud: {} }, hash, null, new anchor.BN(expiresAt))
+      .submitAttestation({ infraCloud: {} }, hash, null, new anchor.BN(expiresAt), 0)
       .accountsPartial({
         config: configPda, authority: authorityPda, agent: agentPda,
-        attestation: attestPda, authoritySigner: walletKeypair.publicKey,
-        systemProgram: SystemProgram.programId
+        attestation: attestPda, enclave: null, previous: null, authoritySigner: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
       }).signers([walletKeypair]).rpc();
     console.log("✅ Attestation submitted! Tx:", tx);
   }
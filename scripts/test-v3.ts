@@ -1,9 +1,10 @@
 
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
-import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { PublicKey, Keypair, SystemProgram, Ed25519Program, SYSVAR_INSTRUCTIONS_PUBKEY } from "@solana/web3.js";
 import * as fs from "fs";
 import * as path from "path";
+import { keccak_256 } from "@noble/hashes/sha3";
 
 const idlPath = path.resolve(process.cwd(), "target/idl/moltlaunch.json");
 const idl = JSON.parse(fs.readFileSync(idlPath, "utf-8"));
@@ -100,6 +101,12 @@ async function main() {
   const [testAgentPda] = PublicKey.findProgramAddressSync(
     [Buffer.from("agent"), testAgent.publicKey.toBytes()], PROGRAM_ID
   );
+  const [testNamePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("name"), Buffer.from("test-agent-v3")], PROGRAM_ID
+  );
+  const [testTombstonePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("tombstone"), testAgent.publicKey.toBytes()], PROGRAM_ID
+  );
 
   try {
     // Fund from our wallet instead of airdrop
@@ -114,9 +121,10 @@ async function main() {
 
     const tx = await program.methods.registerAgent("test-agent-v3")
       .accountsPartial({
-        config: configPda, agent: testAgentPda,
-        wallet: testAgent.publicKey, systemProgram: SystemProgram.programId
-      }).signers([testAgent]).rpc();
+        config: configPda, agent: testAgentPda, nameRecord: testNamePda,
+        tombstone: testTombstonePda, wallet: testAgent.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([testAgent, walletKeypair]).rpc();
     
     const newAgent = await (program.account as any).agentIdentity.fetch(testAgentPda);
     assert(newAgent.name === "test-agent-v3", "Second agent registered with correct name");
@@ -136,19 +144,38 @@ async function main() {
     [Buffer.from("attestation"), testAgent.publicKey.toBytes(), walletKeypair.publicKey.toBytes()], PROGRAM_ID
   );
 
+  const measurement = new Array(32).fill(0);
+  Buffer.from("mock-sgx-mrenclave").forEach((b, i) => { if (i < 32) measurement[i] = b; });
+  const [enclavePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("enclave"), Buffer.from([0, 0]), Buffer.from(measurement)], PROGRAM_ID
+  );
+
   try {
+    try {
+      await (program.account as any).enclaveMeasurement.fetch(enclavePda);
+    } catch {
+      await program.methods
+        .registerEnclaveMeasurement({ intelSgx: {} }, { mrEnclave: {} }, measurement, "mock-sgx")
+        .accountsPartial({
+          config: configPda, enclave: enclavePda, admin: walletKeypair.publicKey,
+          payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+        }).signers([walletKeypair]).rpc();
+    }
+
     const now = Math.floor(Date.now() / 1000);
     const hash = new Array(32).fill(0);
     Buffer.from("test-tee-attestation-v3").forEach((b, i) => { if (i < 32) hash[i] = b; });
-    const teeQuote = new Array(32).fill(0);
-    Buffer.from("mock-sgx-measurement").forEach((b, i) => { if (i < 32) teeQuote[i] = b; });
+    const quoteDigest = new Array(32).fill(0);
+    Buffer.from("mock-sgx-quote").forEach((b, i) => { if (i < 32) quoteDigest[i] = b; });
+    const teeQuote = { vendor: { intelSgx: {} }, kind: { mrEnclave: {} }, measurement, quoteDigest };
 
     const tx = await program.methods
-      .submitAttestation({ infraTee: {} }, hash, teeQuote, new anchor.BN(now + 30 * 24 * 3600))
+      .submitAttestation({ infraTee: {} }, hash, teeQuote, new anchor.BN(now + 30 * 24 * 3600), 0)
       .accountsPartial({
         config: configPda, authority: authPda, agent: testAgentPda,
-        attestation: testAttestPda, authoritySigner: walletKeypair.publicKey,
-        systemProgram: SystemProgram.programId
+        attestation: testAttestPda, enclave: enclavePda, previous: null,
+        authoritySigner: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
       }).signers([walletKeypair]).rpc();
 
     const att = await (program.account as any).attestation.fetch(testAttestPda);
@@ -169,7 +196,10 @@ async function main() {
   try {
     const tx = await program.methods.refreshIdentitySignals()
       .accountsPartial({ config: configPda, agent: testAgentPda })
-      .remainingAccounts([{ pubkey: testAttestPda, isWritable: false, isSigner: false }])
+      .remainingAccounts([
+        { pubkey: testAttestPda, isWritable: false, isSigner: false },
+        { pubkey: enclavePda, isWritable: false, isSigner: false },
+      ])
       .rpc();
 
     const refreshed = await (program.account as any).agentIdentity.fetch(testAgentPda);
@@ -220,9 +250,9 @@ async function main() {
   // ── Test 10: Revoke attestation ──
   console.log("\nTest 10: Revoke Attestation");
   try {
-    const tx = await program.methods.revokeAttestation()
+    const tx = await program.methods.revokeAttestation({ keyRotation: {} }, new Array(32).fill(0))
       .accountsPartial({
-        config: configPda, attestation: testAttestPda,
+        config: configPda, attestation: testAttestPda, agent: testAgentPda,
         authoritySigner: walletKeypair.publicKey
       }).signers([walletKeypair]).rpc();
 
@@ -242,7 +272,10 @@ async function main() {
   try {
     const tx = await program.methods.refreshIdentitySignals()
       .accountsPartial({ config: configPda, agent: testAgentPda })
-      .remainingAccounts([{ pubkey: testAttestPda, isWritable: false, isSigner: false }])
+      .remainingAccounts([
+        { pubkey: testAttestPda, isWritable: false, isSigner: false },
+        { pubkey: enclavePda, isWritable: false, isSigner: false },
+      ])
       .rpc();
 
     const afterRevoke = await (program.account as any).agentIdentity.fetch(testAgentPda);
@@ -255,6 +288,733 @@ async function main() {
     failed++;
   }
 
+  // Helpers for the lifecycle tests below. Our wallet pays all rent, so
+  // throwaway keypairs only ever sign.
+  const expectFail = async (promise: Promise<any>, testName: string) => {
+    try {
+      await promise;
+      assert(false, testName);
+    } catch {
+      assert(true, testName);
+    }
+  };
+  const freshAgent = async () => {
+    const kp = Keypair.generate();
+    // Letters only, without r/n/v, so the name is its own confusable seed
+    const name = "t-" + Array.from({ length: 12 }, () =>
+      "abcdefghijklmopqstuxyz"[Math.floor(Math.random() * 22)]).join("");
+    const [agentPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("agent"), kp.publicKey.toBytes()], PROGRAM_ID
+    );
+    const [namePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("name"), Buffer.from(name)], PROGRAM_ID
+    );
+    const [tombstonePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tombstone"), kp.publicKey.toBytes()], PROGRAM_ID
+    );
+    const [profilePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), kp.publicKey.toBytes()], PROGRAM_ID
+    );
+    await program.methods.registerAgent(name)
+      .accountsPartial({
+        config: configPda, agent: agentPda, nameRecord: namePda,
+        tombstone: tombstonePda, wallet: kp.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([kp, walletKeypair]).rpc();
+    return { kp, name, agentPda, namePda, tombstonePda, profilePda };
+  };
+  const attestationPdaFor = (agentWallet: PublicKey, index: number) => PublicKey.findProgramAddressSync(
+    [Buffer.from("attestation"), agentWallet.toBytes(), walletKeypair.publicKey.toBytes(),
+      ...(index === 0 ? [] : [Buffer.from([index])])], PROGRAM_ID
+  )[0];
+  const attest = async (
+    subject: { kp: Keypair; agentPda: PublicKey }, signal: string, index: number,
+    expiresAt: number, previous: PublicKey | null = null
+  ) => {
+    const attestation = attestationPdaFor(subject.kp.publicKey, index);
+    await program.methods
+      .submitAttestation({ [signal]: {} }, new Array(32).fill(index + 1), null, new anchor.BN(expiresAt), index)
+      .accountsPartial({
+        config: configPda, authority: authPda, agent: subject.agentPda,
+        attestation, enclave: null, previous,
+        authoritySigner: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([walletKeypair]).rpc();
+    return attestation;
+  };
+
+  // ── Test 12: Transfer clears the seller's recovery keys ──
+  console.log("\nTest 12: Transfer Clears Recovery Keys");
+  try {
+    const seller = await freshAgent();
+    const buyer = Keypair.generate();
+    await program.methods.setRecoveryKeys(seller.kp.publicKey, seller.kp.publicKey)
+      .accountsPartial({ config: configPda, agent: seller.agentPda, wallet: seller.kp.publicKey })
+      .signers([seller.kp]).rpc();
+    await program.methods.proposeAgentTransfer(buyer.publicKey)
+      .accountsPartial({ config: configPda, agent: seller.agentPda, wallet: seller.kp.publicKey })
+      .signers([seller.kp]).rpc();
+    await program.methods.acceptAgentTransfer()
+      .accountsPartial({ config: configPda, agent: seller.agentPda, newOwner: buyer.publicKey })
+      .signers([buyer]).rpc();
+
+    const sold = await (program.account as any).agentIdentity.fetch(seller.agentPda);
+    assert(sold.owner.toBase58() === buyer.publicKey.toBase58(), "Buyer owns the agent");
+    assert(sold.emergencyKey === null, "Emergency key cleared");
+    assert(sold.guardian === null && sold.pendingGuardian === null, "Guardian and pending guardian cleared");
+    await expectFail(
+      program.methods.selfFreeze()
+        .accountsPartial({ agent: seller.agentPda, signer: seller.kp.publicKey })
+        .signers([seller.kp]).rpc(),
+      "Seller can no longer freeze the agent"
+    );
+  } catch (e: any) {
+    console.log(`  ❌ Transfer recovery-key test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 13: Agents cannot leave a flagged operator ──
+  console.log("\nTest 13: Unlink From Flagged Operator");
+  try {
+    const member = await freshAgent();
+    const operatorKp = Keypair.generate();
+    const [operatorPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("operator"), operatorKp.publicKey.toBytes()], PROGRAM_ID
+    );
+    await program.methods.createOperator("flagged-fleet")
+      .accountsPartial({
+        config: configPda, operator: operatorPda, operatorAuthority: operatorKp.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([operatorKp, walletKeypair]).rpc();
+    await program.methods.linkOperator()
+      .accountsPartial({
+        config: configPda, operator: operatorPda, agent: member.agentPda,
+        operatorAuthority: operatorKp.publicKey, wallet: member.kp.publicKey
+      }).signers([operatorKp, member.kp]).rpc();
+    await program.methods.flagOperator(new Array(32).fill(0))
+      .accountsPartial({
+        config: configPda, authority: authPda, operator: operatorPda,
+        authoritySigner: walletKeypair.publicKey
+      }).signers([walletKeypair]).rpc();
+
+    await expectFail(
+      program.methods.unlinkOperator()
+        .accountsPartial({ operator: operatorPda, agent: member.agentPda, signer: member.kp.publicKey })
+        .signers([member.kp]).rpc(),
+      "Owner cannot unlink from a flagged operator"
+    );
+    const stillLinked = await (program.account as any).agentIdentity.fetch(member.agentPda);
+    assert(stillLinked.operator?.toBase58() === operatorKp.publicKey.toBase58(), "Agent stays in the flagged fleet");
+  } catch (e: any) {
+    console.log(`  ❌ Flagged operator test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 14: A banned retiree stays retired ──
+  console.log("\nTest 14: Unban Keeps Retired Agents Retired");
+  try {
+    const retiree = await freshAgent();
+    await program.methods.retireAgent()
+      .accountsPartial({ config: configPda, agent: retiree.agentPda, wallet: retiree.kp.publicKey })
+      .signers([retiree.kp]).rpc();
+    await program.methods.banAgent(new Array(32).fill(0))
+      .accountsPartial({ config: configPda, agent: retiree.agentPda, admin: walletKeypair.publicKey })
+      .signers([walletKeypair]).rpc();
+    await program.methods.unbanAgent()
+      .accountsPartial({ config: configPda, agent: retiree.agentPda, admin: walletKeypair.publicKey })
+      .signers([walletKeypair]).rpc();
+    await program.methods.unflagAgent()
+      .accountsPartial({ config: configPda, agent: retiree.agentPda, admin: walletKeypair.publicKey })
+      .signers([walletKeypair]).rpc();
+
+    const after = await (program.account as any).agentIdentity.fetch(retiree.agentPda);
+    assert(JSON.stringify(after.status) === JSON.stringify({ retired: {} }), "Agent is still Retired");
+  } catch (e: any) {
+    console.log(`  ❌ Retired ban test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 15: Fraud record survives close and re-registration ──
+  console.log("\nTest 15: Tombstone Keeps Fraud Revocations");
+  try {
+    const fraudster = await freshAgent();
+    const [fraudAttestPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attestation"), fraudster.kp.publicKey.toBytes(), walletKeypair.publicKey.toBytes()], PROGRAM_ID
+    );
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .submitAttestation({ infraCloud: {} }, new Array(32).fill(1), null, new anchor.BN(now + 3600), 0)
+      .accountsPartial({
+        config: configPda, authority: authPda, agent: fraudster.agentPda,
+        attestation: fraudAttestPda, enclave: null, previous: null,
+        authoritySigner: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([walletKeypair]).rpc();
+    await program.methods.revokeAttestation({ fraud: {} }, new Array(32).fill(0))
+      .accountsPartial({
+        config: configPda, attestation: fraudAttestPda, agent: fraudster.agentPda,
+        authoritySigner: walletKeypair.publicKey
+      }).signers([walletKeypair]).rpc();
+    await program.methods.closeAttestation()
+      .accountsPartial({
+        attestation: fraudAttestPda, rentRecipient: walletKeypair.publicKey,
+        authoritySigner: walletKeypair.publicKey
+      }).signers([walletKeypair]).rpc();
+    await program.methods.closeAgent()
+      .accountsPartial({
+        config: configPda, agent: fraudster.agentPda, nameRecord: fraudster.namePda,
+        profile: fraudster.profilePda, tombstone: fraudster.tombstonePda, wallet: fraudster.kp.publicKey,
+        rentRecipient: walletKeypair.publicKey, nameRentRecipient: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([fraudster.kp, walletKeypair]).rpc();
+
+    const tombstone = await (program.account as any).agentTombstone.fetch(fraudster.tombstonePda);
+    assert(tombstone.fraudRevocations === 1, "Tombstone records one fraud revocation");
+
+    const newName = fraudster.name + "x";
+    const [newNamePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("name"), Buffer.from(newName)], PROGRAM_ID
+    );
+    await program.methods.registerAgent(newName)
+      .accountsPartial({
+        config: configPda, agent: fraudster.agentPda, nameRecord: newNamePda,
+        tombstone: fraudster.tombstonePda, wallet: fraudster.kp.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([fraudster.kp, walletKeypair]).rpc();
+    const reborn = await (program.account as any).agentIdentity.fetch(fraudster.agentPda);
+    assert(reborn.fraudRevocations === 1, "Re-registered agent keeps its fraud revocation");
+  } catch (e: any) {
+    console.log(`  ❌ Tombstone fraud test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 16: A challenge only binds the attestation it was raised against ──
+  console.log("\nTest 16: Challenges Go Stale When The Attestation Is Recreated");
+  try {
+    const target = await freshAgent();
+    const challengerKp = Keypair.generate();
+    const [challengerAuthPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("authority"), challengerKp.publicKey.toBytes()], PROGRAM_ID
+    );
+    await program.methods.addAuthority({ single: {} })
+      .accountsPartial({
+        config: configPda, authority: challengerAuthPda, authorityPubkey: challengerKp.publicKey,
+        admin: walletKeypair.publicKey, payer: walletKeypair.publicKey,
+        systemProgram: SystemProgram.programId
+      }).signers([walletKeypair]).rpc();
+    await program.methods.setChallengePeriod({ infraCloud: {} }, new anchor.BN(3600))
+      .accountsPartial({ config: configPda, admin: walletKeypair.publicKey })
+      .signers([walletKeypair]).rpc();
+
+    const [targetAttestPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attestation"), target.kp.publicKey.toBytes(), walletKeypair.publicKey.toBytes()], PROGRAM_ID
+    );
+    const [challengePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("challenge"), targetAttestPda.toBytes(), challengerKp.publicKey.toBytes()], PROGRAM_ID
+    );
+    const submit = () => program.methods
+      .submitAttestation({ infraCloud: {} }, new Array(32).fill(2), null,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 7200), 0)
+      .accountsPartial({
+        config: configPda, authority: authPda, agent: target.agentPda,
+        attestation: targetAttestPda, enclave: null, previous: null,
+        authoritySigner: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([walletKeypair]).rpc();
+
+    await submit();
+    await program.methods.challengeAttestation(new Array(32).fill(3))
+      .accountsPartial({
+        config: configPda, authority: challengerAuthPda, attestation: targetAttestPda,
+        challenge: challengePda, authoritySigner: challengerKp.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([challengerKp, walletKeypair]).rpc();
+    const original = await (program.account as any).attestation.fetch(targetAttestPda);
+    const challenge = await (program.account as any).attestationChallenge.fetch(challengePda);
+    assert(challenge.attestationCreatedAt.eq(original.createdAt), "Challenge records the attestation's created_at");
+
+    // Replace the attestation at the same address while the challenge is open
+    await program.methods.revokeAttestation({ keyRotation: {} }, new Array(32).fill(0))
+      .accountsPartial({
+        config: configPda, attestation: targetAttestPda, agent: target.agentPda,
+        authoritySigner: walletKeypair.publicKey
+      }).signers([walletKeypair]).rpc();
+    await program.methods.closeAttestation()
+      .accountsPartial({
+        attestation: targetAttestPda, rentRecipient: walletKeypair.publicKey,
+        authoritySigner: walletKeypair.publicKey
+      }).signers([walletKeypair]).rpc();
+    await new Promise((r) => setTimeout(r, 1500));
+    await submit();
+
+    await expectFail(
+      program.methods.resolveChallenge(true)
+        .accountsPartial({
+          config: configPda, challenge: challengePda, attestation: targetAttestPda,
+          agent: target.agentPda, admin: walletKeypair.publicKey
+        }).signers([walletKeypair]).rpc(),
+      "Stale challenge cannot revoke the new attestation"
+    );
+    await program.methods.closeChallenge()
+      .accountsPartial({
+        challenge: challengePda, attestation: targetAttestPda, rentRecipient: walletKeypair.publicKey
+      }).rpc();
+    const replacement = await (program.account as any).attestation.fetch(targetAttestPda);
+    assert(replacement.revoked === false, "Replacement attestation is untouched");
+    assert(replacement.openChallenges === 0, "Replacement attestation carries no challenges");
+    assert((await connection.getAccountInfo(challengePda)) === null, "Stale challenge closed");
+  } catch (e: any) {
+    console.log(`  ❌ Stale challenge test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  } finally {
+    await program.methods.setChallengePeriod({ infraCloud: {} }, new anchor.BN(0))
+      .accountsPartial({ config: configPda, admin: walletKeypair.publicKey })
+      .signers([walletKeypair]).rpc();
+  }
+
+  // ── Test 17: Signed attestations may be relayed out of order ──
+  console.log("\nTest 17: Out-Of-Order Signed Attestation Relays");
+  try {
+    const subject = await freshAgent();
+    const expiresAt = Math.floor(Date.now() / 1000) + 3600;
+    const hash = new Array(32).fill(4);
+    // Mirrors signed_attestation_message for an InfraCloud signal with no
+    // TEE quote and nothing superseded
+    const message = (index: number, nonce: anchor.BN) => Buffer.concat([
+      Buffer.from("MoltLaunch signed attestation v1"),
+      PROGRAM_ID.toBuffer(),
+      subject.kp.publicKey.toBuffer(),
+      Buffer.from([0]),
+      Buffer.from(hash),
+      Buffer.from([0]),
+      new anchor.BN(expiresAt).toTwos(64).toArrayLike(Buffer, "le", 8),
+      Buffer.from([index]),
+      Buffer.from([0]),
+      nonce.toArrayLike(Buffer, "le", 8),
+    ]);
+    const relay = (index: number, nonce: anchor.BN) => {
+      const [attestationPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("attestation"), subject.kp.publicKey.toBytes(), walletKeypair.publicKey.toBytes(),
+          ...(index === 0 ? [] : [Buffer.from([index])])], PROGRAM_ID
+      );
+      return program.methods
+        .submitSignedAttestation({ infraCloud: {} }, hash, null, new anchor.BN(expiresAt), index, nonce)
+        .accountsPartial({
+          config: configPda, authority: authPda, agent: subject.agentPda,
+          attestation: attestationPda, enclave: null, previous: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY, relayer: walletKeypair.publicKey,
+          payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+        })
+        .preInstructions([Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: walletKeypair.secretKey, message: message(index, nonce),
+        })])
+        .signers([walletKeypair]).rpc();
+    };
+
+    const before = await (program.account as any).authority.fetch(authPda);
+    const base = before.lastSignedNonce.addn(10);
+    await relay(1, base.addn(2));
+    await relay(2, base.addn(1));
+    assert(true, "Lower nonce relayed after a higher one");
+    await expectFail(relay(3, base.addn(2)), "Nonce cannot be relayed twice");
+    await expectFail(relay(3, base.addn(1)), "Late nonce cannot be relayed twice");
+    const after = await (program.account as any).authority.fetch(authPda);
+    assert(after.lastSignedNonce.eq(base.addn(2)), "Highest nonce recorded");
+  } catch (e: any) {
+    console.log(`  ❌ Out-of-order relay test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 18: Profiles are closed before their agent ──
+  console.log("\nTest 18: Close Agent Profile");
+  try {
+    const profiled = await freshAgent();
+    await program.methods.createAgentProfile({
+      metadataUri: "https://example.com/agent.json", contentHash: new Array(32).fill(5),
+      model: "test-model", capabilities: ["chat"], endpoints: [], contact: "",
+    }).accountsPartial({
+      config: configPda, agent: profiled.agentPda, profile: profiled.profilePda,
+      wallet: profiled.kp.publicKey, payer: walletKeypair.publicKey,
+      systemProgram: SystemProgram.programId
+    }).signers([profiled.kp, walletKeypair]).rpc();
+
+    const closeAgent = () => program.methods.closeAgent()
+      .accountsPartial({
+        config: configPda, agent: profiled.agentPda, nameRecord: profiled.namePda,
+        profile: profiled.profilePda, tombstone: profiled.tombstonePda, wallet: profiled.kp.publicKey,
+        rentRecipient: walletKeypair.publicKey, nameRentRecipient: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([profiled.kp, walletKeypair]).rpc();
+    await expectFail(closeAgent(), "Agent with a profile cannot be closed");
+
+    await program.methods.closeAgentProfile()
+      .accountsPartial({ agent: profiled.agentPda, profile: profiled.profilePda, wallet: profiled.kp.publicKey })
+      .signers([profiled.kp]).rpc();
+    assert((await connection.getAccountInfo(profiled.profilePda)) === null, "Profile closed");
+    await closeAgent();
+    assert((await connection.getAccountInfo(profiled.agentPda)) === null, "Agent closed once the profile is gone");
+  } catch (e: any) {
+    console.log(`  ❌ Close profile test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 19: Rent goes back to whoever paid it, not the signer ──
+  console.log("\nTest 19: Closes Refund The Payer");
+  try {
+    const sponsored = await freshAgent();
+    const sessionKp = Keypair.generate();
+    const [sessionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("session"), sponsored.kp.publicKey.toBytes(), sessionKp.publicKey.toBytes()], PROGRAM_ID
+    );
+    await program.methods
+      .createSessionKey(sessionKp.publicKey, 1, new anchor.BN(Math.floor(Date.now() / 1000) + 3600))
+      .accountsPartial({
+        config: configPda, agent: sponsored.agentPda, session: sessionPda,
+        wallet: sponsored.kp.publicKey, payer: walletKeypair.publicKey,
+        systemProgram: SystemProgram.programId
+      }).signers([sponsored.kp, walletKeypair]).rpc();
+
+    await expectFail(
+      program.methods.revokeSessionKey()
+        .accountsPartial({
+          agent: sponsored.agentPda, session: sessionPda,
+          rentRecipient: sponsored.kp.publicKey, wallet: sponsored.kp.publicKey
+        }).signers([sponsored.kp]).rpc(),
+      "Signer cannot redirect the session key's rent"
+    );
+    await program.methods.revokeSessionKey()
+      .accountsPartial({
+        agent: sponsored.agentPda, session: sessionPda,
+        rentRecipient: walletKeypair.publicKey, wallet: sponsored.kp.publicKey
+      }).signers([sponsored.kp]).rpc();
+    await program.methods.closeAgent()
+      .accountsPartial({
+        config: configPda, agent: sponsored.agentPda, nameRecord: sponsored.namePda,
+        profile: sponsored.profilePda, tombstone: sponsored.tombstonePda, wallet: sponsored.kp.publicKey,
+        rentRecipient: walletKeypair.publicKey, nameRentRecipient: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([sponsored.kp, walletKeypair]).rpc();
+    assert((await connection.getBalance(sponsored.kp.publicKey)) === 0, "Agent wallet received no rent");
+  } catch (e: any) {
+    console.log(`  ❌ Payer refund test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 20: Batch claims do not collide with direct attestations ──
+  console.log("\nTest 20: Batch Claims Addressed By Batch");
+  try {
+    const claimant = await freshAgent();
+    const expiresAt = Math.floor(Date.now() / 1000) + 3600;
+    const attestationPda = (seed: Buffer) => PublicKey.findProgramAddressSync(
+      [Buffer.from("attestation"), claimant.kp.publicKey.toBytes(), walletKeypair.publicKey.toBytes(), seed],
+      PROGRAM_ID
+    )[0];
+
+    // A direct attestation already occupies index 1
+    const directPda = attestationPda(Buffer.from([1]));
+    await program.methods
+      .submitAttestation({ infraCloud: {} }, new Array(32).fill(6), null, new anchor.BN(expiresAt), 1)
+      .accountsPartial({
+        config: configPda, authority: authPda, agent: claimant.agentPda,
+        attestation: directPda, enclave: null, previous: null,
+        authoritySigner: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([walletKeypair]).rpc();
+
+    // A one-leaf batch at the same index; its root is the leaf itself
+    const hash = new Array(32).fill(7);
+    const leaf = Buffer.from(keccak_256(Buffer.concat([
+      Buffer.from([0]), claimant.kp.publicKey.toBuffer(), Buffer.from([5]), Buffer.from(hash),
+      new anchor.BN(expiresAt).toTwos(64).toArrayLike(Buffer, "le", 8),
+    ])));
+    const batchId = new anchor.BN(Date.now());
+    const [batchPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attestation_batch"), walletKeypair.publicKey.toBytes(), batchId.toArrayLike(Buffer, "le", 8)],
+      PROGRAM_ID
+    );
+    await program.methods
+      .postAttestationBatch(batchId, Array.from(leaf), 1, new anchor.BN(expiresAt), 1)
+      .accountsPartial({
+        config: configPda, authority: authPda, batch: batchPda,
+        authoritySigner: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([walletKeypair]).rpc();
+
+    const claimPda = attestationPda(batchPda.toBuffer());
+    await program.methods
+      .claimBatchAttestation({ general: {} }, hash, new anchor.BN(expiresAt), [])
+      .accountsPartial({
+        config: configPda, authority: authPda, batch: batchPda, agent: claimant.agentPda,
+        attestation: claimPda, previous: null,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([walletKeypair]).rpc();
+
+    const claimed = await (program.account as any).attestation.fetch(claimPda);
+    assert(claimed.batch.toBase58() === batchPda.toBase58(), "Claim lands at the batch-keyed address");
+    const direct = await (program.account as any).attestation.fetch(directPda);
+    assert(Buffer.from(direct.attestationHash).equals(Buffer.alloc(32, 6)), "Direct attestation at the same index untouched");
+  } catch (e: any) {
+    console.log(`  ❌ Batch claim address test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 21: Built-in schemas cannot be deactivated ──
+  console.log("\nTest 21: Built-In Schemas Stay Active");
+  try {
+    const id = Buffer.alloc(4);
+    id.writeUInt32LE(1);
+    const [builtinSchemaPda] = PublicKey.findProgramAddressSync([Buffer.from("schema"), id], PROGRAM_ID);
+    await expectFail(
+      program.methods.setSchemaActive(false)
+        .accountsPartial({ config: configPda, schema: builtinSchemaPda, admin: walletKeypair.publicKey })
+        .signers([walletKeypair]).rpc(),
+      "set_schema_active rejects a built-in schema"
+    );
+    const builtinSchema = await (program.account as any).schema.fetch(builtinSchemaPda);
+    assert(builtinSchema.active === true, "InfraCloud schema still active");
+  } catch (e: any) {
+    console.log(`  ❌ Built-in schema test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 22: Resetting transfers zero the trust score ──
+  console.log("\nTest 22: Transfer Signal Reset Zeroes Trust Score");
+  try {
+    await program.methods.setTransferPolicy(true)
+      .accountsPartial({ config: configPda, admin: walletKeypair.publicKey })
+      .signers([walletKeypair]).rpc();
+    const staked = await freshAgent();
+    const buyer = Keypair.generate();
+    const [stakePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attestation"), staked.kp.publicKey.toBytes(), walletKeypair.publicKey.toBytes()], PROGRAM_ID
+    );
+    await program.methods
+      .submitAttestation({ economicStake: {} }, new Array(32).fill(8), null,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600), 0)
+      .accountsPartial({
+        config: configPda, authority: authPda, agent: staked.agentPda,
+        attestation: stakePda, enclave: null, previous: null,
+        authoritySigner: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      }).signers([walletKeypair]).rpc();
+    await program.methods.refreshIdentitySignals()
+      .accountsPartial({ config: configPda, agent: staked.agentPda })
+      .remainingAccounts([{ pubkey: stakePda, isWritable: false, isSigner: false }])
+      .rpc();
+    const scored = await (program.account as any).agentIdentity.fetch(staked.agentPda);
+    assert(scored.trustScore > 0, "Staked agent has a trust score");
+
+    await program.methods.proposeAgentTransfer(buyer.publicKey)
+      .accountsPartial({ config: configPda, agent: staked.agentPda, wallet: staked.kp.publicKey })
+      .signers([staked.kp]).rpc();
+    await program.methods.acceptAgentTransfer()
+      .accountsPartial({ config: configPda, agent: staked.agentPda, newOwner: buyer.publicKey })
+      .signers([buyer]).rpc();
+    const sold = await (program.account as any).agentIdentity.fetch(staked.agentPda);
+    assert(sold.hasEconomicStake === false, "Economic stake reset");
+    assert(sold.trustScore === 0, "Trust score zeroed in the transfer itself");
+  } catch (e: any) {
+    console.log(`  ❌ Transfer trust score test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  } finally {
+    await program.methods.setTransferPolicy(false)
+      .accountsPartial({ config: configPda, admin: walletKeypair.publicKey })
+      .signers([walletKeypair]).rpc();
+  }
+
+  // ── Test 23: Emergency freeze, guardian delay and admin thaw ──
+  console.log("\nTest 23: Freeze And Thaw");
+  try {
+    const compromised = await freshAgent();
+    const emergencyKp = Keypair.generate();
+    const guardianKp = Keypair.generate();
+    const recoveredOwner = Keypair.generate();
+    await program.methods.setRecoveryKeys(emergencyKp.publicKey, guardianKp.publicKey)
+      .accountsPartial({ config: configPda, agent: compromised.agentPda, wallet: compromised.kp.publicKey })
+      .signers([compromised.kp]).rpc();
+
+    await program.methods.selfFreeze()
+      .accountsPartial({ agent: compromised.agentPda, signer: emergencyKp.publicKey })
+      .signers([emergencyKp]).rpc();
+    const frozen = await (program.account as any).agentIdentity.fetch(compromised.agentPda);
+    assert(frozen.isFrozen === true && frozen.trustScore === 0, "Emergency key froze the agent");
+
+    await expectFail(
+      program.methods.setAttestationConsent(true)
+        .accountsPartial({ config: configPda, agent: compromised.agentPda, wallet: compromised.kp.publicKey })
+        .signers([compromised.kp]).rpc(),
+      "Owner actions are blocked while frozen"
+    );
+    await expectFail(
+      program.methods.thawAgent(null)
+        .accountsPartial({ config: configPda, agent: compromised.agentPda, signer: emergencyKp.publicKey })
+        .signers([emergencyKp]).rpc(),
+      "Emergency key cannot thaw"
+    );
+    await expectFail(
+      program.methods.thawAgent(null)
+        .accountsPartial({ config: configPda, agent: compromised.agentPda, signer: guardianKp.publicKey })
+        .signers([guardianKp]).rpc(),
+      "Guardian cannot thaw before the delay"
+    );
+
+    await program.methods.thawAgent(recoveredOwner.publicKey)
+      .accountsPartial({ config: configPda, agent: compromised.agentPda, signer: walletKeypair.publicKey })
+      .signers([walletKeypair]).rpc();
+    const thawed = await (program.account as any).agentIdentity.fetch(compromised.agentPda);
+    assert(thawed.isFrozen === false, "Admin thawed the agent");
+    assert(thawed.owner.toBase58() === recoveredOwner.publicKey.toBase58(), "Control handed to the new owner");
+  } catch (e: any) {
+    console.log(`  ❌ Freeze and thaw test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 24: Consent mode holds attestations until the owner decides ──
+  console.log("\nTest 24: Attestation Consent");
+  try {
+    const careful = await freshAgent();
+    const hourFromNow = Math.floor(Date.now() / 1000) + 3600;
+    await program.methods.setAttestationConsent(true)
+      .accountsPartial({ config: configPda, agent: careful.agentPda, wallet: careful.kp.publicKey })
+      .signers([careful.kp]).rpc();
+
+    const wanted = await attest(careful, "infraCloud", 0, hourFromNow);
+    const unwanted = await attest(careful, "general", 1, hourFromNow);
+    const held = await (program.account as any).agentIdentity.fetch(careful.agentPda);
+    assert((await (program.account as any).attestation.fetch(wanted)).pending === true, "Attestation lands pending");
+    assert(held.attestationCount === 0 && JSON.stringify(held.infraType) === JSON.stringify({ unknown: {} }),
+      "Pending attestation does not apply its signal");
+
+    await program.methods.acceptAttestation()
+      .accountsPartial({ config: configPda, agent: careful.agentPda, attestation: wanted, wallet: careful.kp.publicKey })
+      .signers([careful.kp]).rpc();
+    const accepted = await (program.account as any).agentIdentity.fetch(careful.agentPda);
+    assert(JSON.stringify(accepted.infraType) === JSON.stringify({ cloud: {} }), "Accepted signal applies");
+
+    await program.methods.rejectAttestation()
+      .accountsPartial({
+        agent: careful.agentPda, attestation: unwanted,
+        rentRecipient: walletKeypair.publicKey, wallet: careful.kp.publicKey
+      }).signers([careful.kp]).rpc();
+    assert((await connection.getAccountInfo(unwanted)) === null, "Rejected attestation closed");
+    const after = await (program.account as any).agentIdentity.fetch(careful.agentPda);
+    assert(after.liveAttestations === 1, "Only the accepted attestation stays live");
+  } catch (e: any) {
+    console.log(`  ❌ Consent test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 25: Anyone may close an expired attestation for a tip ──
+  console.log("\nTest 25: Expired Attestation Crank");
+  const policy = await (program.account as any).protocolConfig.fetch(configPda);
+  try {
+    const lapsing = await freshAgent();
+    const cranker = Keypair.generate();
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      SystemProgram.transfer({
+        fromPubkey: walletKeypair.publicKey, toPubkey: cranker.publicKey, lamports: 0.01 * 1e9,
+      })
+    ));
+    await program.methods.setExpiryCrankPolicy(new anchor.BN(0), new anchor.BN(5000))
+      .accountsPartial({ config: configPda, admin: walletKeypair.publicKey })
+      .signers([walletKeypair]).rpc();
+
+    const expiring = await attest(lapsing, "infraCloud", 0, Math.floor(Date.now() / 1000) + 5);
+    const crank = () => program.methods.closeExpiredAttestation()
+      .accountsPartial({
+        config: configPda, attestation: expiring, agent: lapsing.agentPda,
+        rentRecipient: walletKeypair.publicKey, cranker: cranker.publicKey
+      }).signers([cranker]).rpc();
+    await expectFail(crank(), "Unexpired attestation cannot be cranked");
+
+    await new Promise((r) => setTimeout(r, 10_000));
+    const before = await connection.getBalance(cranker.publicKey);
+    await crank();
+    assert((await connection.getAccountInfo(expiring)) === null, "Expired attestation closed");
+    // Our wallet pays the fee, so the cranker's balance moves by the tip alone
+    assert((await connection.getBalance(cranker.publicKey)) === before + 5000, "Cranker received its tip");
+    const after = await (program.account as any).agentIdentity.fetch(lapsing.agentPda);
+    assert(after.liveAttestations === 0, "Live attestation count released");
+  } catch (e: any) {
+    console.log(`  ❌ Expiry crank test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  } finally {
+    await program.methods.setExpiryCrankPolicy(policy.expiryGracePeriod, policy.crankerTip)
+      .accountsPartial({ config: configPda, admin: walletKeypair.publicKey })
+      .signers([walletKeypair]).rpc();
+  }
+
+  // ── Test 26: Batch attestation skips ineligible entries ──
+  console.log("\nTest 26: Batch Attestation Skips");
+  try {
+    const eligible = await freshAgent();
+    const flagged = await freshAgent();
+    const attested = await freshAgent();
+    const hourFromNow = Math.floor(Date.now() / 1000) + 3600;
+    await program.methods.flagAgent(new Array(32).fill(0))
+      .accountsPartial({
+        config: configPda, authority: authPda, agent: flagged.agentPda,
+        authoritySigner: walletKeypair.publicKey
+      }).signers([walletKeypair]).rpc();
+    const existing = await attest(attested, "general", 2, hourFromNow);
+
+    const entries = [eligible, flagged, attested];
+    await program.methods
+      .submitAttestationsBatch({ infraCloud: {} }, new anchor.BN(hourFromNow), 2,
+        entries.map(() => new Array(32).fill(9)))
+      .accountsPartial({
+        config: configPda, authority: authPda, authoritySigner: walletKeypair.publicKey,
+        payer: walletKeypair.publicKey, systemProgram: SystemProgram.programId
+      })
+      .remainingAccounts(entries.flatMap((entry) => [
+        { pubkey: entry.agentPda, isWritable: true, isSigner: false },
+        { pubkey: attestationPdaFor(entry.kp.publicKey, 2), isWritable: true, isSigner: false },
+      ]))
+      .signers([walletKeypair]).rpc();
+
+    const created = await (program.account as any).attestation.fetch(attestationPdaFor(eligible.kp.publicKey, 2));
+    assert(JSON.stringify(created.signalContributed) === JSON.stringify({ infraCloud: {} }), "Eligible agent attested");
+    assert((await connection.getAccountInfo(attestationPdaFor(flagged.kp.publicKey, 2))) === null,
+      "Flagged agent skipped");
+    const untouched = await (program.account as any).attestation.fetch(existing);
+    assert(JSON.stringify(untouched.signalContributed) === JSON.stringify({ general: {} }),
+      "Existing attestation skipped, not overwritten");
+  } catch (e: any) {
+    console.log(`  ❌ Batch skip test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
+  // ── Test 27: Supersession chains replace, never stack ──
+  console.log("\nTest 27: Attestation Supersession");
+  try {
+    const upgraded = await freshAgent();
+    const hourFromNow = Math.floor(Date.now() / 1000) + 3600;
+    const first = await attest(upgraded, "infraCloud", 0, hourFromNow);
+    const second = await attest(upgraded, "infraCloud", 1, hourFromNow, first);
+
+    const old = await (program.account as any).attestation.fetch(first);
+    const current = await (program.account as any).attestation.fetch(second);
+    assert(old.supersededBy.toBase58() === second.toBase58(), "Old attestation points at its replacement");
+    assert(old.revoked === true && JSON.stringify(old.revocationReason) === JSON.stringify({ superseded: {} }),
+      "Old attestation revoked as Superseded");
+    assert(current.supersedes.toBase58() === first.toBase58() && current.sequence === 1,
+      "Replacement records its predecessor and sequence");
+    const agentAfter = await (program.account as any).agentIdentity.fetch(upgraded.agentPda);
+    assert(agentAfter.liveAttestations === 1, "Only the replacement stays live");
+
+    await expectFail(
+      attest(upgraded, "infraCloud", 2, hourFromNow, first),
+      "An attestation cannot be superseded twice"
+    );
+    await expectFail(
+      attest(upgraded, "general", 3, hourFromNow, second),
+      "A different signal cannot supersede"
+    );
+  } catch (e: any) {
+    console.log(`  ❌ Supersession test failed: ${e.message?.slice(0, 150)}`);
+    failed++;
+  }
+
   // ── Summary ──
   console.log(`\n${"=".repeat(40)}`);
   console.log(`RESULTS: ${passed} passed, ${failed} failed, ${passed + failed} total`);